@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 use crate::{board_selector::SelectedBoard, GridPosition};
 
@@ -17,10 +17,12 @@ pub enum GridAxis {
     Y,
 }
 
-pub fn get_random_pos_on_axis(axis: GridAxis, selected_board: &Res<SelectedBoard>) -> i32 {
+pub fn get_random_pos_on_axis(
+    axis: GridAxis,
+    selected_board: &Res<SelectedBoard>,
+    rng: &mut impl Rng,
+) -> i32 {
     // get a random integer in the range of the number of tiles in the specified axis
-    let mut rng = thread_rng();
-
     match axis {
         GridAxis::X => rng
             .gen_range(0..selected_board.board.tiles.shape()[1])
@@ -0,0 +1,526 @@
+use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::asset::io::file::FileAssetReader;
+use bevy::{prelude::*, window::FileDragAndDrop};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    phases::GameState,
+    player::Player,
+    theme::ChosenTheme,
+    ui::{ChosenLanguage, Language},
+    GameSettings,
+};
+
+const PROFILE_PATH: &str = "player_profile.ron";
+const FONT_SIZE: f32 = 24.0;
+const TOKEN_SIZE: f32 = 56.0;
+const RING_WIDTH: f32 = 4.0;
+const RING_BUTTON_SIZE: f32 = 28.0;
+
+// The tokens shipped with the game
+const TOKEN_SPRITES: [&str; 4] = [
+    "players/Commoner.png",
+    "players/Genie.png",
+    "players/Harengon.png",
+    "players/Speaker.png",
+];
+
+// The colors of the ring around a token
+const RING_COLORS: [Color; 8] = [
+    Color::rgb(0.9, 0.2, 0.2),
+    Color::rgb(0.2, 0.5, 1.0),
+    Color::rgb(0.2, 0.8, 0.3),
+    Color::rgb(1.0, 0.85, 0.1),
+    Color::rgb(0.7, 0.3, 0.9),
+    Color::rgb(1.0, 0.55, 0.1),
+    Color::rgb(0.95, 0.95, 0.95),
+    Color::rgb(0.1, 0.1, 0.1),
+];
+
+// The image files a player can drop on the window to use as a token
+const IMAGE_EXTENSIONS: [&str; 2] = ["png", "webp"];
+// Where they are copied to, in the assets folder
+const CUSTOM_TOKENS_FOLDER: &str = "players/custom";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Token {
+    // index in the tokens shipped with the game
+    Sprite(usize),
+    // path of an image given by the player, once copied in the assets folder
+    Image(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Avatar {
+    pub token: Token,
+    // index in the ring colors
+    pub ring: usize,
+}
+
+impl Avatar {
+    // The first token and ring, from the ones of the seat, that the seats before it do not have
+    fn default_after(before: &[Avatar]) -> Self {
+        let seat = before.len();
+        let token = (seat..seat + TOKEN_SPRITES.len())
+            .map(|index| Token::Sprite(index % TOKEN_SPRITES.len()))
+            .find(|token| before.iter().all(|other| other.token != *token))
+            .unwrap_or(Token::Sprite(seat % TOKEN_SPRITES.len()));
+        let ring = (seat..seat + RING_COLORS.len())
+            .map(|ring| ring % RING_COLORS.len())
+            .find(|&ring| before.iter().all(|other| other.ring != ring))
+            .unwrap_or(seat % RING_COLORS.len());
+        Avatar { token, ring }
+    }
+
+    pub fn token_path(&self) -> String {
+        match &self.token {
+            Token::Sprite(index) => TOKEN_SPRITES[index % TOKEN_SPRITES.len()].to_string(),
+            Token::Image(path) => path.clone(),
+        }
+    }
+
+    pub fn ring_color(&self) -> Color {
+        RING_COLORS[self.ring % RING_COLORS.len()]
+    }
+}
+
+// The avatar of each seat, kept from one game to the next
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    avatars: Vec<Avatar>,
+}
+
+impl PlayerProfile {
+    fn load() -> Self {
+        // There is no file system on the web, the avatars only last for the session there
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(mut profile) = std::fs::read_to_string(PROFILE_PATH)
+            .ok()
+            .and_then(|text| ron::from_str::<PlayerProfile>(&text).ok())
+        {
+            profile.remove_duplicates();
+            return profile;
+        }
+        PlayerProfile::default()
+    }
+
+    fn save(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(text) => {
+                if let Err(error) = std::fs::write(PROFILE_PATH, text) {
+                    warn!("Could not save the player profile: {error}");
+                }
+            }
+            Err(error) => warn!("Could not serialize the player profile: {error}"),
+        }
+    }
+
+    pub fn avatar(&self, seat: i32) -> Avatar {
+        match self.avatars.get(seat as usize) {
+            Some(avatar) => avatar.clone(),
+            None => {
+                let mut avatars = self.avatars.clone();
+                fill_avatars(&mut avatars, seat as usize + 1);
+                avatars.swap_remove(seat as usize)
+            }
+        }
+    }
+
+    fn avatar_mut(&mut self, seat: i32) -> &mut Avatar {
+        fill_avatars(&mut self.avatars, seat as usize + 1);
+        &mut self.avatars[seat as usize]
+    }
+
+    fn token_taken(&self, token: &Token, seat: i32, num_seats: i32) -> bool {
+        (0..num_seats).any(|other| other != seat && self.avatar(other).token == *token)
+    }
+
+    fn ring_taken(&self, ring: usize, seat: i32, num_seats: i32) -> bool {
+        (0..num_seats).any(|other| other != seat && self.avatar(other).ring == ring)
+    }
+
+    fn next_token(&mut self, seat: i32, num_seats: i32) {
+        // the next shipped token no other seat has, an image goes back to the first one
+        let start = match self.avatar(seat).token {
+            Token::Sprite(index) => index + 1,
+            Token::Image(_) => 0,
+        };
+        let free = (start..start + TOKEN_SPRITES.len())
+            .map(|index| Token::Sprite(index % TOKEN_SPRITES.len()))
+            .find(|token| !self.token_taken(token, seat, num_seats));
+        if let Some(token) = free {
+            self.avatar_mut(seat).token = token;
+        }
+    }
+
+    fn next_ring(&mut self, seat: i32, num_seats: i32) {
+        let start = self.avatar(seat).ring + 1;
+        let free = (start..start + RING_COLORS.len())
+            .map(|ring| ring % RING_COLORS.len())
+            .find(|&ring| !self.ring_taken(ring, seat, num_seats));
+        if let Some(ring) = free {
+            self.avatar_mut(seat).ring = ring;
+        }
+    }
+
+    // Whether the image could be used, another seat may already have it
+    fn use_image(&mut self, seat: i32, num_seats: i32, path: String) -> bool {
+        let token = Token::Image(path);
+        if self.token_taken(&token, seat, num_seats) {
+            return false;
+        }
+        self.avatar_mut(seat).token = token;
+        true
+    }
+
+    fn remove_duplicates(&mut self) {
+        // A hand-edited profile may give the same token or ring to two seats
+        let num_seats = self.avatars.len() as i32;
+        for seat in 0..num_seats {
+            let avatar = self.avatar(seat);
+            if (0..seat).any(|other| self.avatar(other).token == avatar.token) {
+                self.next_token(seat, num_seats);
+            }
+            if (0..seat).any(|other| self.avatar(other).ring == avatar.ring) {
+                self.next_ring(seat, num_seats);
+            }
+        }
+    }
+}
+
+// The seats left out of the profile get the default avatars
+fn fill_avatars(avatars: &mut Vec<Avatar>, num_seats: usize) {
+    while avatars.len() < num_seats {
+        let next = Avatar::default_after(avatars);
+        avatars.push(next);
+    }
+}
+
+#[derive(Component, Debug)]
+struct AvatarPicker;
+
+#[derive(Component, Debug)]
+struct TokenButton {
+    seat: i32,
+}
+
+#[derive(Component, Debug)]
+struct RingButton {
+    seat: i32,
+}
+
+#[derive(Component, Debug)]
+struct DropText;
+
+// The seat a dropped image goes to, the last one whose token was clicked
+#[derive(Resource, Debug, Default)]
+struct DropSeat {
+    seat: i32,
+    // whether the last dropped image was refused
+    refused: bool,
+}
+
+pub struct AvatarsPlugin;
+
+impl Plugin for AvatarsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PlayerProfile::load())
+            .insert_resource(DropSeat::default())
+            .add_systems(OnEnter(GameState::MainMenu), spawn_avatar_picker)
+            .add_systems(
+                Update,
+                (
+                    press_avatar_buttons,
+                    drop_token_images,
+                    respawn_avatar_picker,
+                    update_drop_text,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::MainMenu)),
+            )
+            .add_systems(OnExit(GameState::MainMenu), cleanup_avatar_picker)
+            .add_systems(Update, ring_tokens.run_if(in_state(GameState::Playing)));
+    }
+}
+
+fn spawn_avatar_picker(
+    mut commands: Commands,
+    profile: Res<PlayerProfile>,
+    game_settings: Res<GameSettings>,
+    language: Res<ChosenLanguage>,
+    theme: Res<ChosenTheme>,
+    asset_server: Res<AssetServer>,
+) {
+    build_avatar_picker(
+        &mut commands,
+        &profile,
+        &game_settings,
+        &language,
+        &theme,
+        &asset_server,
+    );
+}
+
+fn build_avatar_picker(
+    commands: &mut Commands,
+    profile: &PlayerProfile,
+    game_settings: &GameSettings,
+    language: &ChosenLanguage,
+    theme: &ChosenTheme,
+    asset_server: &AssetServer,
+) {
+    let colors = theme.get().colors;
+    let text_style = TextStyle {
+        font_size: FONT_SIZE,
+        color: colors.text,
+        ..default()
+    };
+    let title = match language.lang {
+        Language::French => "Pions (cliquer pour changer)",
+        Language::English => "Tokens (click to change)",
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::VMax(0.5),
+                    top: Val::VMin(1.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: colors.panel.into(),
+                ..default()
+            },
+            AvatarPicker,
+        ))
+        .with_children(|panel| {
+            panel.spawn(TextBundle::from_section(title, text_style.clone()));
+            for seat in 0..game_settings.num_players {
+                let avatar = profile.avatar(seat);
+                let name = match language.lang {
+                    Language::French => format!("Joueur {}", seat + 1),
+                    Language::English => format!("Player {}", seat + 1),
+                };
+                panel
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(TOKEN_SIZE),
+                                    height: Val::Px(TOKEN_SIZE),
+                                    border: UiRect::all(Val::Px(RING_WIDTH)),
+                                    ..default()
+                                },
+                                border_color: avatar.ring_color().into(),
+                                // not to tint the token
+                                background_color: Color::WHITE.into(),
+                                image: asset_server.load(avatar.token_path()).into(),
+                                ..default()
+                            },
+                            TokenButton { seat },
+                        ));
+                        row.spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(RING_BUTTON_SIZE),
+                                    height: Val::Px(RING_BUTTON_SIZE),
+                                    ..default()
+                                },
+                                background_color: avatar.ring_color().into(),
+                                ..default()
+                            },
+                            RingButton { seat },
+                        ));
+                        row.spawn(TextBundle::from_section(name, text_style.clone()));
+                    });
+            }
+            panel.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: FONT_SIZE * 0.8,
+                        ..text_style.clone()
+                    },
+                ),
+                DropText,
+            ));
+        });
+}
+
+fn press_avatar_buttons(
+    token_query: Query<(&Interaction, &TokenButton), Changed<Interaction>>,
+    ring_query: Query<(&Interaction, &RingButton), Changed<Interaction>>,
+    mut profile: ResMut<PlayerProfile>,
+    mut drop_seat: ResMut<DropSeat>,
+    game_settings: Res<GameSettings>,
+) {
+    // No two seats can pick the same token or the same ring
+    for (interaction, button) in &token_query {
+        if *interaction == Interaction::Pressed {
+            profile.next_token(button.seat, game_settings.num_players);
+            profile.save();
+            *drop_seat = DropSeat {
+                seat: button.seat,
+                refused: false,
+            };
+        }
+    }
+    for (interaction, button) in &ring_query {
+        if *interaction == Interaction::Pressed {
+            profile.next_ring(button.seat, game_settings.num_players);
+            profile.save();
+        }
+    }
+}
+
+fn drop_token_images(
+    mut events: EventReader<FileDragAndDrop>,
+    mut profile: ResMut<PlayerProfile>,
+    mut drop_seat: ResMut<DropSeat>,
+    game_settings: Res<GameSettings>,
+) {
+    for event in events.read() {
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = event {
+            let path = path_buf
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map(str::to_lowercase)
+                .filter(|extension| IMAGE_EXTENSIONS.contains(&extension.as_str()))
+                .and_then(|extension| import_token_image(path_buf, &extension));
+            drop_seat.refused = !path.is_some_and(|path| {
+                profile.use_image(drop_seat.seat, game_settings.num_players, path)
+            });
+            if !drop_seat.refused {
+                profile.save();
+            }
+        }
+    }
+}
+
+// Copies a dropped image where the asset server reads from, and gives its path there
+// Named after its content, so that dropping the same image again gives the same token
+#[cfg(not(target_arch = "wasm32"))]
+fn import_token_image(source: &Path, extension: &str) -> Option<String> {
+    let bytes = std::fs::read(source)
+        .map_err(|error| warn!("Could not read the image {}: {error}", source.display()))
+        .ok()?;
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    let path = format!(
+        "{CUSTOM_TOKENS_FOLDER}/{:016x}.{extension}",
+        hasher.finish()
+    );
+
+    // the default folder of the asset plugin
+    let assets = FileAssetReader::get_base_path().join("assets");
+    std::fs::create_dir_all(assets.join(CUSTOM_TOKENS_FOLDER))
+        .and_then(|()| std::fs::write(assets.join(&path), bytes))
+        .map_err(|error| warn!("Could not copy the image {}: {error}", source.display()))
+        .ok()?;
+    Some(path)
+}
+
+// There is no file system on the web
+#[cfg(target_arch = "wasm32")]
+fn import_token_image(_source: &Path, _extension: &str) -> Option<String> {
+    None
+}
+
+#[allow(clippy::too_many_arguments)]
+fn respawn_avatar_picker(
+    mut commands: Commands,
+    picker_query: Query<Entity, With<AvatarPicker>>,
+    profile: Res<PlayerProfile>,
+    game_settings: Res<GameSettings>,
+    language: Res<ChosenLanguage>,
+    theme: Res<ChosenTheme>,
+    asset_server: Res<AssetServer>,
+) {
+    // Rebuild the picker to show the new avatars, or to translate or recolor it
+    if profile.is_changed() || language.is_changed() || theme.is_changed() {
+        for entity in &picker_query {
+            commands.entity(entity).despawn_recursive();
+        }
+        build_avatar_picker(
+            &mut commands,
+            &profile,
+            &game_settings,
+            &language,
+            &theme,
+            &asset_server,
+        );
+    }
+}
+
+fn update_drop_text(
+    mut text_query: Query<&mut Text, With<DropText>>,
+    drop_seat: Res<DropSeat>,
+    language: Res<ChosenLanguage>,
+) {
+    let player = drop_seat.seat + 1;
+    let value = match (&language.lang, drop_seat.refused) {
+        (Language::French, false) => {
+            format!("Deposer une image (png, webp)\nsur la fenetre: pion du joueur {player}")
+        }
+        (Language::French, true) => "Image refusee, ou deja prise".to_string(),
+        (Language::English, false) => {
+            format!("Drop an image (png, webp)\non the window: token of player {player}")
+        }
+        (Language::English, true) => "Image refused, or already taken".to_string(),
+    };
+    for mut text in &mut text_query {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+fn cleanup_avatar_picker(mut commands: Commands, picker_query: Query<Entity, With<AvatarPicker>>) {
+    for entity in &picker_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn ring_tokens(
+    mut commands: Commands,
+    player_query: Query<(Entity, &Player), Added<Player>>,
+    profile: Res<PlayerProfile>,
+    theme: Res<ChosenTheme>,
+    asset_server: Res<AssetServer>,
+) {
+    // The ring comes from the theme, in the color picked for the seat
+    for (entity, player) in &player_query {
+        commands.entity(entity).with_children(|token| {
+            // around the token, scaled with it, and just above it
+            token.spawn(SpriteBundle {
+                texture: asset_server.load(theme.get().token_frame()),
+                sprite: Sprite {
+                    color: profile.avatar(player.id).ring_color(),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                ..default()
+            });
+        });
+    }
+}
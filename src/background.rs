@@ -0,0 +1,48 @@
+// Long computations, like the puzzle solver, run off the main thread so the game keeps drawing
+// There are no threads on the web, the work is done at once there
+
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+
+#[derive(Debug)]
+pub struct Background<T> {
+    #[cfg(not(target_arch = "wasm32"))]
+    task: Task<T>,
+    #[cfg(target_arch = "wasm32")]
+    result: T,
+}
+
+impl<T: Send + 'static> Background<T> {
+    pub fn spawn(work: impl FnOnce() -> T + Send + 'static) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        return Background {
+            task: AsyncComputeTaskPool::get().spawn(async move { work() }),
+        };
+        #[cfg(target_arch = "wasm32")]
+        return Background { result: work() };
+    }
+
+    pub fn is_finished(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.task.is_finished();
+        #[cfg(target_arch = "wasm32")]
+        return true;
+    }
+
+    // Blocks until the work is done, so only once it is finished or when it can't wait
+    pub fn wait(self) -> T {
+        #[cfg(not(target_arch = "wasm32"))]
+        return block_on(self.task);
+        #[cfg(target_arch = "wasm32")]
+        return self.result;
+    }
+
+    // The result if the work is done, or else the work itself back
+    pub fn poll(self) -> Result<T, Self> {
+        if self.is_finished() {
+            Ok(self.wait())
+        } else {
+            Err(self)
+        }
+    }
+}
@@ -0,0 +1,6 @@
+// Bots play complete games against each other, without a window, and the stats are printed
+// `cargo run --release --bin simulate -- --games 100`, see `simulate_from_args` for the options
+
+fn main() {
+    labby::simulate_from_args();
+}
@@ -0,0 +1,6 @@
+// Prints the solutions of puzzles, without a window
+// `cargo run --release --bin solve -- --puzzle <puzzle name, seed or all>`
+
+fn main() {
+    labby::solve_from_args();
+}
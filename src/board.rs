@@ -1,29 +1,162 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, transform::TransformSystem};
 use ndarray::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
 
 use crate::actors::SpawnPosition;
 use crate::board_selector::SelectedBoard;
-use crate::movement::CanMove;
+use crate::movement::{CanMove, Direction};
 use crate::phases::GameState;
+use crate::rng::GameRng;
 use crate::tile::spawn_tile;
+use crate::tile::sync_tile_orientation;
+use crate::tile::Orientation;
 use crate::tile::TileType;
+use crate::tile::{reset_rotated_round, rotate_rotators, RotatedRound};
+use crate::topology::Topology;
+use crate::GridPosition;
 
 const GROUND_SIZE: Vec3 = Vec3::new(1600.0, 1600.0, 0.0);
 
+// A square of the ground around the board
+#[derive(Component, Debug)]
+pub struct Ground;
+
 /// The general internal board representation
 #[derive(Debug, Clone)]
 pub struct Board {
     pub tiles: Array2<TileInfo>,
     pub spawn_positions: Vec<SpawnPosition>,
     pub treasure_positions: Vec<SpawnPosition>,
-    pub external_tile: TileInfo,
+    // The spare tiles, the first one starts in the first push slot and the others in the pool
+    pub external_tiles: Vec<TileInfo>,
+    // The movable tiles dealt into the Any cells and the external tiles, as (type, count)
+    // Any cells left once the deck is empty get a random type
+    pub tile_deck: Vec<(TileType, usize)>,
+    // The shape of the cells, square unless the board is a hex one
+    pub topology: Topology,
+}
+
+// A place the spare tile can be pushed from
+#[derive(Debug, Clone)]
+pub struct PushSlot {
+    pub pos: GridPosition,
+    // The way the tiles are pushed
+    pub dir: Direction,
+    // The pushed tiles, from the slot to the other end of the line or the next void
+    pub line: Vec<GridPosition>,
+    // Where the last tile of the line is pushed out to
+    pub exit: GridPosition,
+}
+
+impl Board {
+    pub fn max_coords(&self) -> (i32, i32) {
+        (
+            self.tiles.shape()[1] as i32 - 1,
+            self.tiles.shape()[0] as i32 - 1,
+        )
+    }
+
+    fn cell(&self, pos: &GridPosition) -> Option<&TileInfo> {
+        // the board array is stored top-down
+        let (max_x, max_y) = self.max_coords();
+        if pos.x_pos < 0 || pos.y_pos < 0 || pos.x_pos > max_x || pos.y_pos > max_y {
+            return None;
+        }
+        self.tiles
+            .get([(max_y - pos.y_pos) as usize, pos.x_pos as usize])
+    }
+
+    pub fn is_void(&self, pos: &GridPosition) -> bool {
+        self.cell(pos)
+            .is_some_and(|tile| tile.tile_type == TileType::Void)
+    }
+
+    // Whether there is a board tile at this position
+    pub fn contains(&self, pos: &GridPosition) -> bool {
+        self.cell(pos)
+            .is_some_and(|tile| tile.tile_type != TileType::Void)
+    }
+
+    // Where the spare tiles that are not in a push slot wait, in a column left of the board
+    pub fn spare_pool(&self) -> Vec<GridPosition> {
+        let (_, max_y) = self.max_coords();
+        (1..self.external_tiles.len() as i32)
+            .map(|index| GridPosition {
+                x_pos: -3,
+                y_pos: max_y + 1 - index,
+            })
+            .collect()
+    }
+
+    pub fn push_slots(&self) -> Vec<PushSlot> {
+        // A line of tiles between two voids (or the edges of the board) can be pushed from both
+        // of its ends, as long as none of its tiles is fixed
+        let num_tiles_y = self.tiles.shape()[0] as i32;
+        let num_tiles_x = self.tiles.shape()[1] as i32;
+        let mut slots = vec![];
+
+        for &dir in self.topology.directions() {
+            let (step_x, step_y) = dir.offset();
+            // the lines are walked from the side the tiles are pushed from,
+            // starting next to the cells whose previous cell is off the board array
+            let starts = (0..num_tiles_y)
+                .flat_map(|y_pos| (0..num_tiles_x).map(move |x_pos| GridPosition { x_pos, y_pos }))
+                .map(|pos| GridPosition {
+                    x_pos: pos.x_pos - step_x,
+                    y_pos: pos.y_pos - step_y,
+                })
+                .filter(|start| self.cell(start).is_none())
+                .collect::<Vec<_>>();
+
+            for start in starts {
+                let mut pos = start;
+                let mut line: Vec<GridPosition> = vec![];
+                loop {
+                    let next = GridPosition {
+                        x_pos: pos.x_pos + step_x,
+                        y_pos: pos.y_pos + step_y,
+                    };
+                    if self.contains(&next) {
+                        line.push(next);
+                    } else {
+                        if !line.is_empty() {
+                            let slot_pos = GridPosition {
+                                x_pos: line[0].x_pos - step_x,
+                                y_pos: line[0].y_pos - step_y,
+                            };
+                            let fixed = line.iter().any(|tile_pos| {
+                                self.cell(tile_pos)
+                                    .is_some_and(|tile| matches!(tile.can_move, CanMove::No))
+                            });
+                            if !fixed {
+                                slots.push(PushSlot {
+                                    pos: slot_pos,
+                                    dir,
+                                    line: line.clone(),
+                                    exit: next,
+                                });
+                            }
+                            line.clear();
+                        }
+                        if self.cell(&next).is_none() {
+                            // out of the board
+                            break;
+                        }
+                    }
+                    pos = next;
+                }
+            }
+        }
+        slots
+    }
 }
 
 // All of the info needed to spawn a tile
 #[derive(Debug, Default, Clone, Copy)]
 pub struct TileInfo {
     pub tile_type: TileType,
-    pub angle: f32,
+    pub orientation: Orientation,
     pub can_move: CanMove,
 }
 
@@ -31,24 +164,38 @@ pub struct BoardPlugin;
 
 impl Plugin for BoardPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_ground)
-            .add_systems(OnEnter(GameState::Playing), spawn_board)
-            .add_systems(OnExit(GameState::Playing), cleanup_board);
+        app.init_resource::<RotatedRound>()
+            .add_systems(Startup, spawn_ground)
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (spawn_board, reset_rotated_round),
+            )
+            .add_systems(OnExit(GameState::Playing), cleanup_board)
+            // Before the transforms are propagated, so that a rotation is drawn on the same frame
+            .add_systems(
+                PostUpdate,
+                sync_tile_orientation.before(TransformSystem::TransformPropagate),
+            )
+            .add_systems(Update, rotate_rotators.run_if(in_state(GameState::Playing)));
     }
 }
 
 fn spawn_board(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
     selected_board: Res<SelectedBoard>,
+    mut game_rng: ResMut<GameRng>,
 ) {
-    let board = &selected_board.board;
+    let board = &deal_tiles(&selected_board.board, &mut game_rng.tiles);
     let num_tiles_y = board.tiles.shape()[0];
     let num_tiles_x = board.tiles.shape()[1];
 
     for y_pos in 0..num_tiles_y {
         for x_pos in 0..num_tiles_x {
             let current_tile = board.tiles[[y_pos, x_pos]];
+            if current_tile.tile_type == TileType::Void {
+                // hole in the board
+                continue;
+            }
             // we need to invert the y index
             // to spawn tiles top-down from the selected board array
             // Note: This panics if the board is immense
@@ -57,25 +204,61 @@ fn spawn_board(
             spawn_tile(
                 int_x_pos,
                 int_y_pos,
-                current_tile.tile_type,
-                current_tile.angle,
-                current_tile.can_move,
+                current_tile,
                 &mut commands,
-                &asset_server,
+                board.topology,
+                &mut game_rng.tiles,
             );
         }
     }
 
-    // External tile for pushing
-    spawn_tile(
-        -1,
-        0,
-        board.external_tile.tile_type,
-        board.external_tile.angle,
-        board.external_tile.can_move,
-        &mut commands,
-        &asset_server,
+    // External tiles for pushing, in the first push slot and then in the pool
+    let spare_pos = board.push_slots().first().map_or(
+        GridPosition {
+            x_pos: -1,
+            y_pos: 0,
+        },
+        |slot| slot.pos,
     );
+    for (pos, tile_info) in std::iter::once(spare_pos)
+        .chain(board.spare_pool())
+        .zip(&board.external_tiles)
+    {
+        spawn_tile(
+            pos.x_pos,
+            pos.y_pos,
+            *tile_info,
+            &mut commands,
+            board.topology,
+            &mut game_rng.tiles,
+        );
+    }
+}
+
+pub fn deal_tiles(board: &Board, rng: &mut impl Rng) -> Board {
+    // Replace the Any tiles of the board by tiles drawn from its deck, with random rotations
+    let mut deck = board
+        .tile_deck
+        .iter()
+        .flat_map(|&(tile_type, count)| std::iter::repeat_n(tile_type, count))
+        .collect::<Vec<TileType>>();
+    deck.shuffle(rng);
+
+    let mut dealt_board = board.clone();
+    for tile in dealt_board
+        .tiles
+        .iter_mut()
+        .chain(dealt_board.external_tiles.iter_mut())
+    {
+        if tile.tile_type == TileType::Any {
+            if let Some(tile_type) = deck.pop() {
+                tile.tile_type = tile_type;
+                tile.orientation =
+                    Orientation::new(rng.gen_range(0..board.topology.sides() as i32));
+            }
+        }
+    }
+    dealt_board
 }
 
 fn cleanup_board(mut commands: Commands, tiles_query: Query<Entity, With<TileType>>) {
@@ -84,21 +267,24 @@ fn cleanup_board(mut commands: Commands, tiles_query: Query<Entity, With<TileTyp
     }
 }
 
-fn spawn_ground(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn spawn_ground(mut commands: Commands) {
+    // The texture comes from the theme, once it is loaded
     for x in -30..31 {
         for y in -30..31 {
-            commands.spawn(SpriteBundle {
-                texture: asset_server.load("ground.png"),
-                transform: Transform {
-                    translation: Vec3::new(
-                        x as f32 * GROUND_SIZE.x,
-                        y as f32 * GROUND_SIZE.y,
-                        -1.0,
-                    ),
+            commands.spawn((
+                SpriteBundle {
+                    transform: Transform {
+                        translation: Vec3::new(
+                            x as f32 * GROUND_SIZE.x,
+                            y as f32 * GROUND_SIZE.y,
+                            -1.0,
+                        ),
+                        ..default()
+                    },
                     ..default()
                 },
-                ..default()
-            });
+                Ground,
+            ));
         }
     }
 }
@@ -3,25 +3,56 @@ use once_cell::sync::Lazy;
 
 use crate::{
     board::Board,
-    boards_repository::{BOARD_0, BOARD_CLASSIC},
+    boards_repository::{
+        BOARD_0, BOARD_BIG, BOARD_CLASSIC, BOARD_CROSS, BOARD_HEX, BOARD_ONE_WAY, BOARD_SPECIAL,
+    },
+    phases::GameState,
 };
 
 #[derive(Resource)]
 pub struct SelectedBoard {
     pub board: Board,
+    // index of the board in the list of available boards
+    pub index: usize,
 }
 
 pub struct BoardSelectorPlugin;
 
 impl Plugin for BoardSelectorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreStartup, select_board);
+        app.add_systems(PreStartup, select_board)
+            .add_systems(Update, cycle_boards.run_if(in_state(GameState::MainMenu)));
     }
 }
 
+fn available_boards() -> [&'static Lazy<Board>; 7] {
+    [
+        &BOARD_CLASSIC,
+        &BOARD_0,
+        &BOARD_SPECIAL,
+        &BOARD_ONE_WAY,
+        &BOARD_CROSS,
+        &BOARD_HEX,
+        &BOARD_BIG,
+    ]
+}
+
 fn select_board(mut commands: Commands) {
     // Select board on startup from repository
     commands.insert_resource(SelectedBoard {
-        board: Lazy::force(&BOARD_CLASSIC).clone(),
+        board: Lazy::force(available_boards()[0]).clone(),
+        index: 0,
     })
 }
+
+fn cycle_boards(mut selected_board: ResMut<SelectedBoard>, keys: Res<Input<KeyCode>>) {
+    // Switch to the next available board from the main menu
+    if keys.just_pressed(KeyCode::B) {
+        let boards = available_boards();
+        let index = (selected_board.index + 1) % boards.len();
+        *selected_board = SelectedBoard {
+            board: Lazy::force(boards[index]).clone(),
+            index,
+        };
+    }
+}
@@ -1,12 +1,13 @@
 use ndarray::prelude::*;
 use once_cell::sync::Lazy;
-use std::f32::consts::PI;
 
 use crate::actors::SpawnPosition;
 use crate::board::Board;
 use crate::board::TileInfo;
 use crate::movement::CanMove;
+use crate::tile::Orientation;
 use crate::tile::TileType;
+use crate::topology::Topology;
 use crate::GridPosition;
 
 // Set board with set tiles and Any tiles, and spawn positions
@@ -14,24 +15,24 @@ pub static BOARD_0: Lazy<Board> = Lazy::new(|| Board {
     tiles: array![
         [
             TileInfo {
-                tile_type: TileType::Corner,
-                angle: PI / 2.0,
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(1),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::T,
-                angle: 0.0,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(0),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::Corner,
-                angle: 0.0,
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(0),
                 can_move: CanMove::No,
             },
         ],
@@ -54,8 +55,8 @@ pub static BOARD_0: Lazy<Board> = Lazy::new(|| Board {
         ],
         [
             TileInfo {
-                tile_type: TileType::T,
-                angle: PI / 2.0,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(1),
                 can_move: CanMove::No,
             },
             TileInfo {
@@ -68,8 +69,8 @@ pub static BOARD_0: Lazy<Board> = Lazy::new(|| Board {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::T,
-                angle: -PI / 2.0,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(3),
                 can_move: CanMove::No,
             },
         ],
@@ -92,24 +93,24 @@ pub static BOARD_0: Lazy<Board> = Lazy::new(|| Board {
         ],
         [
             TileInfo {
-                tile_type: TileType::Corner,
-                angle: PI,
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(2),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::T,
-                angle: PI,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(2),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::Corner,
-                angle: -PI / 2.0,
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(3),
                 can_move: CanMove::No,
             },
         ],
@@ -126,43 +127,194 @@ pub static BOARD_0: Lazy<Board> = Lazy::new(|| Board {
         SpawnPosition::Any,
         SpawnPosition::Any,
     ],
-    external_tile: TileInfo {
-        tile_type: TileType::Corner,
-        angle: 0.0,
+    external_tiles: vec![TileInfo {
         ..Default::default()
-    },
+    }],
+    tile_deck: vec![
+        (TileType::named("straight"), 6),
+        (TileType::named("corner"), 8),
+        (TileType::named("t_shape"), 4),
+    ],
+    topology: Topology::Square,
+});
+
+// Board 0 dealt with teleporter, bridge and rotator tiles among the plain ones
+pub static BOARD_SPECIAL: Lazy<Board> = Lazy::new(|| Board {
+    tile_deck: vec![
+        (TileType::named("straight"), 4),
+        (TileType::named("corner"), 6),
+        (TileType::named("t_shape"), 2),
+        (TileType::named("teleporter_blue"), 2),
+        (TileType::named("bridge"), 2),
+        (TileType::named("rotator"), 2),
+    ],
+    ..Lazy::force(&BOARD_0).clone()
+});
+
+// Board with a clockwise ring of one-way passages, a blocked and a dead end tile
+pub static BOARD_ONE_WAY: Lazy<Board> = Lazy::new(|| Board {
+    tiles: array![
+        [
+            TileInfo {
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(1),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("one_way"),
+                orientation: Orientation::new(0),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(0),
+                can_move: CanMove::No,
+            },
+        ],
+        [
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("block"),
+                orientation: Orientation::new(0),
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                tile_type: TileType::named("one_way"),
+                orientation: Orientation::new(1),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("four_way"),
+                orientation: Orientation::new(0),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("one_way"),
+                orientation: Orientation::new(3),
+                can_move: CanMove::No,
+            },
+        ],
+        [
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("dead_end"),
+                orientation: Orientation::new(0),
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(2),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("one_way"),
+                orientation: Orientation::new(2),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(3),
+                can_move: CanMove::No,
+            },
+        ],
+    ],
+    spawn_positions: vec![
+        SpawnPosition::Position(GridPosition { x_pos: 0, y_pos: 0 }),
+        SpawnPosition::Position(GridPosition { x_pos: 0, y_pos: 4 }),
+        SpawnPosition::Position(GridPosition { x_pos: 4, y_pos: 4 }),
+        SpawnPosition::Position(GridPosition { x_pos: 4, y_pos: 0 }),
+    ],
+    treasure_positions: vec![
+        SpawnPosition::Any,
+        SpawnPosition::Any,
+        SpawnPosition::Any,
+        SpawnPosition::Any,
+    ],
+    external_tiles: vec![TileInfo {
+        ..Default::default()
+    }],
+    tile_deck: vec![
+        (TileType::named("straight"), 5),
+        (TileType::named("corner"), 6),
+        (TileType::named("t_shape"), 4),
+    ],
+    topology: Topology::Square,
 });
 
 pub static BOARD_CLASSIC: Lazy<Board> = Lazy::new(|| Board {
     tiles: array![
         [
             TileInfo {
-                tile_type: TileType::Corner,
-                angle: PI / 2.0,
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(1),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::T,
-                angle: 0.0,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(0),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::T,
-                angle: 0.0,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(0),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::Corner,
-                angle: 0.0,
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(0),
                 can_move: CanMove::No,
             },
         ],
@@ -191,32 +343,32 @@ pub static BOARD_CLASSIC: Lazy<Board> = Lazy::new(|| Board {
         ],
         [
             TileInfo {
-                tile_type: TileType::T,
-                angle: PI / 2.0,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(1),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::T,
-                angle: PI / 2.0,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(1),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::T,
-                angle: 0.0,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(0),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::T,
-                angle: -PI / 2.0,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(3),
                 can_move: CanMove::No,
             },
         ],
@@ -245,32 +397,32 @@ pub static BOARD_CLASSIC: Lazy<Board> = Lazy::new(|| Board {
         ],
         [
             TileInfo {
-                tile_type: TileType::T,
-                angle: PI / 2.0,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(1),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::T,
-                angle: PI,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(2),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::T,
-                angle: -PI / 2.0,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(3),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::T,
-                angle: -PI / 2.0,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(3),
                 can_move: CanMove::No,
             },
         ],
@@ -299,32 +451,32 @@ pub static BOARD_CLASSIC: Lazy<Board> = Lazy::new(|| Board {
         ],
         [
             TileInfo {
-                tile_type: TileType::Corner,
-                angle: PI,
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(2),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::T,
-                angle: PI,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(2),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::T,
-                angle: PI,
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(2),
                 can_move: CanMove::No,
             },
             TileInfo {
                 ..Default::default()
             },
             TileInfo {
-                tile_type: TileType::Corner,
-                angle: -PI / 2.0,
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(3),
                 can_move: CanMove::No,
             },
         ],
@@ -341,9 +493,790 @@ pub static BOARD_CLASSIC: Lazy<Board> = Lazy::new(|| Board {
         SpawnPosition::Any,
         SpawnPosition::Any,
     ],
-    external_tile: TileInfo {
-        tile_type: TileType::Corner,
-        angle: 0.0,
+    external_tiles: vec![TileInfo {
         ..Default::default()
-    },
+    }],
+    tile_deck: vec![
+        (TileType::named("straight"), 12),
+        (TileType::named("corner"), 16),
+        (TileType::named("t_shape"), 6),
+    ],
+    topology: Topology::Square,
+});
+
+// Cross shaped board with a hole in the middle, its lines are pushed up to the holes
+pub static BOARD_CROSS: Lazy<Board> = Lazy::new(|| Board {
+    tiles: array![
+        [
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(0),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(3),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(1),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(2),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+        ],
+    ],
+    spawn_positions: vec![
+        SpawnPosition::Position(GridPosition { x_pos: 3, y_pos: 0 }),
+        SpawnPosition::Position(GridPosition { x_pos: 0, y_pos: 3 }),
+        SpawnPosition::Position(GridPosition { x_pos: 3, y_pos: 6 }),
+        SpawnPosition::Position(GridPosition { x_pos: 6, y_pos: 3 }),
+    ],
+    treasure_positions: vec![
+        SpawnPosition::Any,
+        SpawnPosition::Any,
+        SpawnPosition::Any,
+        SpawnPosition::Any,
+    ],
+    external_tiles: vec![TileInfo {
+        ..Default::default()
+    }],
+    tile_deck: vec![
+        (TileType::named("straight"), 9),
+        (TileType::named("corner"), 10),
+        (TileType::named("t_shape"), 6),
+        (TileType::named("teleporter_blue"), 2),
+        (TileType::named("bridge"), 2),
+    ],
+    topology: Topology::Square,
+});
+
+// Hexagonal board, in axial coordinates: each row is shifted half a tile to the right of the one
+// under it, and the corners of the array are holes
+pub static BOARD_HEX: Lazy<Board> = Lazy::new(|| Board {
+    tiles: array![
+        [
+            TileInfo {
+                tile_type: TileType::named("hex_y"),
+                orientation: Orientation::new(2),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("hex_y"),
+                orientation: Orientation::new(1),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                tile_type: TileType::named("hex_y"),
+                orientation: Orientation::new(3),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("hex_fork"),
+                orientation: Orientation::new(0),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("hex_y"),
+                orientation: Orientation::new(0),
+                can_move: CanMove::No,
+            },
+        ],
+        [
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::Void,
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("hex_y"),
+                orientation: Orientation::new(4),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("hex_y"),
+                orientation: Orientation::new(5),
+                can_move: CanMove::No,
+            },
+        ],
+    ],
+    spawn_positions: vec![
+        SpawnPosition::Position(GridPosition { x_pos: 3, y_pos: 0 }),
+        SpawnPosition::Position(GridPosition { x_pos: 0, y_pos: 6 }),
+        SpawnPosition::Position(GridPosition { x_pos: 3, y_pos: 6 }),
+        SpawnPosition::Position(GridPosition { x_pos: 6, y_pos: 0 }),
+    ],
+    treasure_positions: vec![
+        SpawnPosition::Any,
+        SpawnPosition::Any,
+        SpawnPosition::Any,
+        SpawnPosition::Any,
+    ],
+    external_tiles: vec![TileInfo {
+        ..Default::default()
+    }],
+    tile_deck: vec![
+        (TileType::named("hex_straight"), 8),
+        (TileType::named("hex_bend"), 10),
+        (TileType::named("hex_sharp"), 5),
+        (TileType::named("hex_y"), 8),
+    ],
+    topology: Topology::Hex,
+});
+
+// Big classic-like board, with three spare tiles to choose from
+pub static BOARD_BIG: Lazy<Board> = Lazy::new(|| Board {
+    tiles: array![
+        [
+            TileInfo {
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(1),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(0),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(0),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(0),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(0),
+                can_move: CanMove::No,
+            },
+        ],
+        [
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(1),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(1),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(0),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(0),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(3),
+                can_move: CanMove::No,
+            },
+        ],
+        [
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(1),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(1),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("four_way"),
+                orientation: Orientation::new(0),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(3),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(3),
+                can_move: CanMove::No,
+            },
+        ],
+        [
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(1),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(2),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(2),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(3),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(3),
+                can_move: CanMove::No,
+            },
+        ],
+        [
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                ..Default::default()
+            },
+        ],
+        [
+            TileInfo {
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(2),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(2),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(2),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("t_shape"),
+                orientation: Orientation::new(2),
+                can_move: CanMove::No,
+            },
+            TileInfo {
+                ..Default::default()
+            },
+            TileInfo {
+                tile_type: TileType::named("corner"),
+                orientation: Orientation::new(3),
+                can_move: CanMove::No,
+            },
+        ],
+    ],
+    spawn_positions: vec![
+        SpawnPosition::Position(GridPosition { x_pos: 0, y_pos: 0 }),
+        SpawnPosition::Position(GridPosition { x_pos: 0, y_pos: 8 }),
+        SpawnPosition::Position(GridPosition { x_pos: 8, y_pos: 8 }),
+        SpawnPosition::Position(GridPosition { x_pos: 8, y_pos: 0 }),
+    ],
+    treasure_positions: vec![
+        SpawnPosition::Any,
+        SpawnPosition::Any,
+        SpawnPosition::Any,
+        SpawnPosition::Any,
+    ],
+    external_tiles: vec![
+        TileInfo {
+            ..Default::default()
+        },
+        TileInfo {
+            ..Default::default()
+        },
+        TileInfo {
+            ..Default::default()
+        },
+    ],
+    tile_deck: vec![
+        (TileType::named("straight"), 21),
+        (TileType::named("corner"), 26),
+        (TileType::named("t_shape"), 12),
+    ],
+    topology: Topology::Square,
 });
@@ -0,0 +1,754 @@
+// Seats played by external programs, that talk with the game through their standard input
+// and output, one line at a time, in the spirit of the UCI protocol of chess engines
+//
+// Start the game with `--bot <seat>=<command>` (seats counted from 1) for each bot seat
+//
+// When it starts, the game sends `labby <version>` and the bot answers `ready`
+// On each of its turns, the bot is sent the position:
+//   position
+//   topology <square|hex>
+//   size <width> <height>
+//   directions <direction>...       the order of the sides in the cells below
+//   row <y> <cell>...               one line per row from the top, cells from x = 0
+//                                   a cell is `-` without a tile, or else one digit per side,
+//                                   1 when open, followed by `>` and the ways in when they differ
+//                                   (one-way tiles) and by `/` and the lower passage on bridges
+//   spare <cell>                    the spare tile, as it is turned now
+//   slot <index> <x> <y> <direction>  one line per push slot: the cell the spare tile is pushed
+//                                   from, and the way the tiles of the line move
+//   illegal <index>...              the slots that can't be pushed from this turn
+//   player <id> <x> <y> <over|under>  one line per player, the level being the passage of a bridge
+//   you <id>
+//   target <x> <y>                  one line per treasure the bot may collect,
+//   target spare                    or this one for a treasure on the spare tile
+//   go
+// and it answers `move <clockwise turns> <slot index> <x> <y>`: how many times to turn the spare
+// tile, where to push it from, and where to walk after the push
+// Empty lines and lines starting with `info` are ignored, so bots can log
+// The game sends `quit` before closing
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use bevy::{
+    app::AppExit, ecs::system::SystemParam, prelude::*, transform::TransformSystem, utils::HashMap,
+};
+
+use crate::{
+    board::{Board, PushSlot},
+    board_selector::SelectedBoard,
+    movement::{
+        move_stacks, push_from_current_slot, BridgeLevel, Direction, IllegalPushPositions,
+        PushSlots, PushedEntitiesQuery, SpareStacksQuery, TilePassages,
+    },
+    phases::{end_turn, GameState},
+    player::Player,
+    simulation::arg_values,
+    solver::{walks_from, SolverState, SolverTilesQuery},
+    sound::SoundEffect,
+    teams::Teams,
+    tile::{get_oriented_ways, OpenWays, Orientation, TileType},
+    treasure::{Treasure, TreasureLists},
+    GamePhase, GameSettings, GridPosition,
+};
+
+pub const PROTOCOL_VERSION: u32 = 1;
+// How long a bot may take to start
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+// How long a bot has to leave once asked to, before it is stopped
+const QUIT_TIMEOUT: Duration = Duration::from_millis(100);
+// How long a bot may think about a move, before the default push is played for it
+pub const BOT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// The position sent to a bot at the start of its turn
+pub struct BotPosition<'a> {
+    pub board: &'a Board,
+    pub slots: &'a [PushSlot],
+    // the tiles and the spare tile, the player and treasure in it are not used
+    pub state: &'a SolverState,
+    pub illegal: Vec<usize>,
+    pub players: Vec<(i32, (GridPosition, BridgeLevel))>,
+    pub you: i32,
+    // None for a treasure on the spare tile
+    pub targets: Vec<Option<GridPosition>>,
+}
+
+impl BotPosition<'_> {
+    pub fn lines(&self) -> Vec<String> {
+        let topology = self.board.topology;
+        let directions = topology.mask_directions();
+        let name = |dir: &Direction| format!("{dir:?}").to_lowercase();
+        let mask = |ways: OpenWays| {
+            directions
+                .iter()
+                .map(|&dir| if ways.is_open(dir) { '1' } else { '0' })
+                .collect::<String>()
+        };
+        let cell = |passages: &TilePassages| {
+            let mut text = mask(passages.open_ways);
+            let entry = mask(passages.entry_ways.0);
+            if entry != text {
+                text = format!("{text}>{entry}");
+            }
+            if directions
+                .iter()
+                .any(|&dir| passages.under_ways.0.is_open(dir))
+            {
+                text = format!("{text}/{}", mask(passages.under_ways.0));
+            }
+            text
+        };
+
+        let (max_x, max_y) = self.board.max_coords();
+        let passages = self.state.passages(self.board);
+        let mut lines = vec![
+            "position".to_string(),
+            format!("topology {}", format!("{topology:?}").to_lowercase()),
+            format!("size {} {}", max_x + 1, max_y + 1),
+            format!(
+                "directions {}",
+                directions.iter().map(name).collect::<Vec<_>>().join(" ")
+            ),
+        ];
+        for y_pos in (0..=max_y).rev() {
+            let cells = (0..=max_x)
+                .map(|x_pos| {
+                    passages
+                        .get(&GridPosition { x_pos, y_pos })
+                        .map_or("-".to_string(), cell)
+                })
+                .collect::<Vec<_>>();
+            lines.push(format!("row {y_pos} {}", cells.join(" ")));
+        }
+        let (spare_type, spare_orientation) = self.state.spare;
+        let (open_ways, entry_ways, under_ways) = get_oriented_ways(spare_type, spare_orientation);
+        lines.push(format!(
+            "spare {}",
+            cell(&TilePassages {
+                tile_type: spare_type,
+                open_ways,
+                entry_ways,
+                under_ways,
+            })
+        ));
+        for (index, slot) in self.slots.iter().enumerate() {
+            lines.push(format!(
+                "slot {index} {} {} {}",
+                slot.pos.x_pos,
+                slot.pos.y_pos,
+                name(&slot.dir)
+            ));
+        }
+        let illegal = self.illegal.iter().map(|index| format!(" {index}"));
+        lines.push(format!("illegal{}", illegal.collect::<String>()));
+        for (id, (pos, level)) in &self.players {
+            let level = format!("{level:?}").to_lowercase();
+            lines.push(format!("player {id} {} {} {level}", pos.x_pos, pos.y_pos));
+        }
+        lines.push(format!("you {}", self.you));
+        for target in &self.targets {
+            lines.push(match target {
+                Some(pos) => format!("target {} {}", pos.x_pos, pos.y_pos),
+                None => "target spare".to_string(),
+            });
+        }
+        lines.push("go".to_string());
+        lines
+    }
+}
+
+// The answer of a bot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BotMove {
+    pub clockwise_turns: u8,
+    pub slot: usize,
+    pub destination: GridPosition,
+}
+
+impl BotMove {
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        if words.next()? != "move" {
+            return None;
+        }
+        let mut number = || words.next()?.parse::<i64>().ok();
+        let (turns, slot, x_pos, y_pos) = (number()?, number()?, number()?, number()?);
+        Some(BotMove {
+            clockwise_turns: turns.rem_euclid(12) as u8,
+            slot: usize::try_from(slot).ok()?,
+            destination: GridPosition {
+                x_pos: i32::try_from(x_pos).ok()?,
+                y_pos: i32::try_from(y_pos).ok()?,
+            },
+        })
+    }
+
+    // The orientation of the spare tile once turned as asked
+    pub fn orientation(&self, spare: Orientation) -> Orientation {
+        (0..self.clockwise_turns).fold(spare, |orientation, _| orientation.rotated_clockwise())
+    }
+}
+
+// What a bot wrote since it was last asked
+pub enum BotAnswer {
+    Line(String),
+    Pending,
+    // the bot closed its output, it won't answer anymore
+    Gone,
+}
+
+// A running bot program
+#[derive(Debug)]
+pub struct ExternalBot {
+    command: String,
+    // only taken when the bot is dropped
+    child: Option<Child>,
+    stdin: ChildStdin,
+    // the lines written by the bot, read on another thread so the game never waits for them
+    replies: Mutex<Receiver<String>>,
+}
+
+impl ExternalBot {
+    // Starts the bot and waits for it to be ready
+    pub fn start(command: &str) -> io::Result<Self> {
+        let bot = ExternalBot::spawn(command)?;
+        match bot.wait_answer(HANDSHAKE_TIMEOUT) {
+            Some(line) if line == "ready" => Ok(bot),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{command} did not answer ready"),
+            )),
+        }
+    }
+
+    // Starts the bot and greets it, its answer is read like the others
+    pub fn spawn(command: &str) -> io::Result<Self> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty bot command"))?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "no bot pipes"));
+        };
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let line = line.trim().to_string();
+                if line.is_empty() || line.starts_with("info") {
+                    continue;
+                }
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut bot = ExternalBot {
+            command: command.to_string(),
+            child: Some(child),
+            stdin,
+            replies: Mutex::new(receiver),
+        };
+        bot.send(&[format!("labby {PROTOCOL_VERSION}")])?;
+        Ok(bot)
+    }
+
+    pub fn send(&mut self, lines: &[String]) -> io::Result<()> {
+        for line in lines {
+            writeln!(self.stdin, "{line}")?;
+        }
+        self.stdin.flush()
+    }
+
+    pub fn answer(&self) -> BotAnswer {
+        let Ok(replies) = self.replies.lock() else {
+            return BotAnswer::Gone;
+        };
+        match replies.try_recv() {
+            Ok(line) => BotAnswer::Line(line),
+            Err(TryRecvError::Empty) => BotAnswer::Pending,
+            Err(TryRecvError::Disconnected) => BotAnswer::Gone,
+        }
+    }
+
+    pub fn wait_answer(&self, timeout: Duration) -> Option<String> {
+        self.replies.lock().ok()?.recv_timeout(timeout).ok()
+    }
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        // Ask the bot to leave, and make sure it does, on another thread so the game doesn't wait
+        let _ = self.send(&["quit".to_string()]);
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+        let command = self.command.clone();
+        thread::spawn(move || {
+            thread::sleep(QUIT_TIMEOUT);
+            if !matches!(child.try_wait(), Ok(Some(_))) {
+                warn!("Stopping the bot {command}");
+                let _ = child.kill();
+            }
+            let _ = child.wait();
+        });
+    }
+}
+
+// The `--bot <seat>=<command>` arguments, as (player id, command)
+pub fn bot_commands_from_args() -> Vec<(i32, String)> {
+    let args = std::env::args().collect::<Vec<_>>();
+    arg_values(&args, "--bot")
+        .iter()
+        .filter_map(|value| {
+            let (seat, command) = value.split_once('=')?;
+            Some((seat.trim().parse::<i32>().ok()? - 1, command.to_string()))
+        })
+        .collect()
+}
+
+// Where the current bot is in its turn, a step a frame at most
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum BotStep {
+    #[default]
+    Idle,
+    // since the time the bot was asked
+    Thinking(Duration),
+    // the spare tile is in place, no destination when the answer could not be used
+    Pushing(Option<GridPosition>),
+    Walking(Option<GridPosition>),
+    // the turn ends on the next frame, once a treasure under the bot had a chance to be collected
+    Done,
+}
+
+// The seats played by bots in the game
+#[derive(Resource, Debug, Default)]
+pub struct ExternalBots {
+    seats: HashMap<i32, ExternalBot>,
+    // the seats whose bot has not answered ready yet, with the time it was started at
+    handshakes: HashMap<i32, Duration>,
+    step: BotStep,
+}
+
+impl ExternalBots {
+    pub fn drives(&self, player_id: i32) -> bool {
+        self.seats.contains_key(&player_id)
+    }
+}
+
+// Run condition of the input systems, which are not for the seats played by bots
+pub fn human_is_playing(bots: Res<ExternalBots>, game_state: Res<GamePhase>) -> bool {
+    !bots.drives(game_state.current_player_id)
+}
+
+pub struct BotsPlugin;
+
+impl Plugin for BotsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ExternalBots::default())
+            .add_systems(Startup, connect_bots)
+            .add_systems(Update, finish_handshakes)
+            // The game leaves without dropping its resources, so the bots are told here
+            .add_systems(Last, quit_bots)
+            // After the game systems, so that a treasure walked onto is collected before the turn
+            // is ended here
+            .add_systems(
+                PostUpdate,
+                (
+                    end_bot_turn,
+                    ask_bot,
+                    read_bot_answer,
+                    push_for_bot,
+                    walk_for_bot,
+                )
+                    .chain()
+                    .before(TransformSystem::TransformPropagate)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), reset_bot_step);
+    }
+}
+
+fn connect_bots(mut bots: ResMut<ExternalBots>, time: Res<Time>) {
+    // Their answers are waited for over the next frames, a seat is played as soon as it is ready
+    for (id, command) in bot_commands_from_args() {
+        match ExternalBot::spawn(&command) {
+            Ok(bot) => {
+                bots.seats.insert(id, bot);
+                bots.handshakes.insert(id, time.elapsed());
+            }
+            Err(error) => warn!("Could not start the bot of seat {}: {error}", id + 1),
+        }
+    }
+}
+
+fn finish_handshakes(mut bots: ResMut<ExternalBots>, time: Res<Time>) {
+    let handshakes = bots
+        .handshakes
+        .iter()
+        .map(|(&id, &started)| (id, started))
+        .collect::<Vec<_>>();
+    for (id, started) in handshakes {
+        let answer = bots
+            .seats
+            .get(&id)
+            .map_or(BotAnswer::Gone, ExternalBot::answer);
+        let ready = match answer {
+            BotAnswer::Pending if time.elapsed() - started < HANDSHAKE_TIMEOUT => continue,
+            BotAnswer::Line(line) => line == "ready",
+            BotAnswer::Pending | BotAnswer::Gone => false,
+        };
+        bots.handshakes.remove(&id);
+        // A seat without a bot is played by a person
+        if !ready {
+            warn!("The bot of seat {} did not answer ready", id + 1);
+            bots.seats.remove(&id);
+        }
+    }
+}
+
+fn quit_bots(mut bots: ResMut<ExternalBots>, mut exits: EventReader<AppExit>) {
+    if exits.read().next().is_some() {
+        bots.seats.clear();
+    }
+}
+
+fn reset_bot_step(mut bots: ResMut<ExternalBots>) {
+    bots.step = BotStep::Idle;
+}
+
+// What the game knows, as a bot needs it
+#[derive(SystemParam)]
+struct LivePosition<'w, 's> {
+    tiles_query: SolverTilesQuery<'w, 's>,
+    player_query: Query<'w, 's, (&'static Player, &'static GridPosition, &'static BridgeLevel)>,
+    treasure_query: Query<'w, 's, (&'static Treasure, &'static GridPosition)>,
+    treasure_lists: Res<'w, TreasureLists>,
+    game_settings: Res<'w, GameSettings>,
+    selected_board: Res<'w, SelectedBoard>,
+    push_slots: Res<'w, PushSlots>,
+    illegal: Res<'w, IllegalPushPositions>,
+}
+
+impl LivePosition<'_, '_> {
+    fn lines(&self, you: i32) -> Vec<String> {
+        let board = &self.selected_board.board;
+        let spare_pos = self
+            .push_slots
+            .slots
+            .get(self.push_slots.current)
+            .map(|slot| slot.pos);
+        let spare = self
+            .tiles_query
+            .iter()
+            .find(|(pos, ..)| Some(**pos) == spare_pos)
+            .map_or(
+                (TileType::Void, Orientation::default()),
+                |(_, tile_type, orientation)| (*tile_type, *orientation),
+            );
+        let state = SolverState::new(
+            board,
+            self.tiles_query
+                .iter()
+                .map(|(pos, tile_type, orientation)| (*pos, *tile_type, *orientation)),
+            spare,
+            (GridPosition::default(), BridgeLevel::Over),
+            None,
+            None,
+        );
+
+        // Alone, the treasures are collected in order
+        let teams = self.game_settings.teams;
+        let deck = self
+            .treasure_lists
+            .lists
+            .get(&teams.team_of(you))
+            .cloned()
+            .unwrap_or_default();
+        let wanted = match teams {
+            Teams::Solo => deck.last().copied().into_iter().collect(),
+            Teams::Pairs { .. } | Teams::Coop => deck,
+        };
+        let targets = self
+            .treasure_query
+            .iter()
+            .filter(|(treasure, _)| wanted.contains(&treasure.id))
+            .map(|(_, pos)| Some(*pos).filter(|pos| board.contains(pos)))
+            .collect();
+
+        let mut players = self
+            .player_query
+            .iter()
+            .map(|(player, pos, level)| (player.id, (*pos, *level)))
+            .collect::<Vec<_>>();
+        players.sort_by_key(|(id, _)| *id);
+        BotPosition {
+            board,
+            slots: &self.push_slots.slots,
+            state: &state,
+            illegal: (0..self.push_slots.slots.len())
+                .filter(|&slot| self.illegal.is_illegal(slot))
+                .collect(),
+            players,
+            you,
+            targets,
+        }
+        .lines()
+    }
+}
+
+fn ask_bot(
+    mut bots: ResMut<ExternalBots>,
+    position: LivePosition,
+    game_state: Res<GamePhase>,
+    time: Res<Time>,
+) {
+    let current = game_state.current_player_id;
+    if bots.step != BotStep::Idle
+        || !game_state.tile_push_phase
+        || game_state.has_ended
+        || !bots.drives(current)
+        || bots.handshakes.contains_key(&current)
+    {
+        return;
+    }
+    let lines = position.lines(current);
+    let sent = bots.seats.get_mut(&current).map(|bot| {
+        // An answer that came too late for an earlier turn is not for this one
+        while let BotAnswer::Line(_) = bot.answer() {}
+        bot.send(&lines)
+    });
+    bots.step = match sent {
+        Some(Ok(())) => BotStep::Thinking(time.elapsed()),
+        _ => {
+            warn!("The bot of seat {} can't be reached", current + 1);
+            BotStep::Pushing(None)
+        }
+    };
+}
+
+fn read_bot_answer(
+    mut bots: ResMut<ExternalBots>,
+    mut stacks_query: SpareStacksQuery,
+    mut push_slots: ResMut<PushSlots>,
+    illegal: Res<IllegalPushPositions>,
+    game_state: Res<GamePhase>,
+    time: Res<Time>,
+) {
+    let current = game_state.current_player_id;
+    let BotStep::Thinking(asked_at) = bots.step else {
+        return;
+    };
+    // The tile was already pushed, or the game ended
+    if !game_state.tile_push_phase || game_state.has_ended {
+        bots.step = BotStep::Idle;
+        return;
+    }
+    let answer = bots
+        .seats
+        .get(&current)
+        .map_or(BotAnswer::Gone, ExternalBot::answer);
+    let chosen = match answer {
+        BotAnswer::Pending if time.elapsed() - asked_at < BOT_TIMEOUT => return,
+        BotAnswer::Pending => {
+            warn!("The bot of seat {} took too long to answer", current + 1);
+            None
+        }
+        BotAnswer::Line(line) => {
+            let chosen = BotMove::parse(&line).filter(|chosen| {
+                chosen.slot < push_slots.slots.len() && !illegal.is_illegal(chosen.slot)
+            });
+            if chosen.is_none() {
+                warn!("Unusable answer of the bot of seat {}: {line}", current + 1);
+            }
+            chosen
+        }
+        BotAnswer::Gone => {
+            warn!("The bot of seat {} stopped answering", current + 1);
+            None
+        }
+    };
+    // Without a move to play, the spare tile is pushed as it is from the first legal slot
+    let Some(slot) = chosen
+        .map(|chosen| chosen.slot)
+        .or_else(|| (0..push_slots.slots.len()).find(|&slot| !illegal.is_illegal(slot)))
+    else {
+        bots.step = BotStep::Walking(None);
+        return;
+    };
+
+    // Turn the spare tile and bring it to the slot, as a player would
+    let spare_pos = push_slots.slots[push_slots.current].pos;
+    for (grid_pos, _, orientation) in &mut stacks_query {
+        if let (true, Some(mut orientation), Some(chosen)) =
+            (*grid_pos == spare_pos, orientation, chosen)
+        {
+            *orientation = chosen.orientation(*orientation);
+        }
+    }
+    let topology = push_slots.topology;
+    move_stacks(
+        &mut stacks_query,
+        &[(spare_pos, push_slots.slots[slot].pos)],
+        topology,
+    );
+    push_slots.current = slot;
+    bots.step = BotStep::Pushing(chosen.map(|chosen| chosen.destination));
+}
+
+fn push_for_bot(
+    mut bots: ResMut<ExternalBots>,
+    mut entities_query: PushedEntitiesQuery,
+    mut push_slots: ResMut<PushSlots>,
+    mut illegal: ResMut<IllegalPushPositions>,
+    mut game_state: ResMut<GamePhase>,
+    mut sounds: EventWriter<SoundEffect>,
+) {
+    let BotStep::Pushing(destination) = bots.step else {
+        return;
+    };
+    push_from_current_slot(&mut push_slots, &mut entities_query, &mut illegal);
+    sounds.send(SoundEffect::TileSlide);
+    game_state.tile_push_phase = false;
+    bots.step = BotStep::Walking(destination);
+}
+
+fn walk_for_bot(
+    mut bots: ResMut<ExternalBots>,
+    tiles_query: SolverTilesQuery,
+    mut player_query: Query<(&Player, &mut GridPosition, &mut Transform, &mut BridgeLevel)>,
+    selected_board: Res<SelectedBoard>,
+    game_state: Res<GamePhase>,
+    mut sounds: EventWriter<SoundEffect>,
+) {
+    let BotStep::Walking(destination) = bots.step else {
+        return;
+    };
+    bots.step = BotStep::Done;
+    let Some(destination) = destination else {
+        return;
+    };
+    let board = &selected_board.board;
+    for (player, mut grid_pos, mut transform, mut level) in &mut player_query {
+        if player.id != game_state.current_player_id {
+            continue;
+        }
+        // The bot can only go where the player could walk to
+        let state = SolverState::new(
+            board,
+            tiles_query
+                .iter()
+                .map(|(pos, tile_type, orientation)| (*pos, *tile_type, *orientation)),
+            (TileType::Void, Orientation::default()),
+            (*grid_pos, *level),
+            None,
+            None,
+        );
+        let walks = walks_from(state.player, &state.passages(board), board);
+        let reached = [BridgeLevel::Over, BridgeLevel::Under]
+            .into_iter()
+            .find(|level| walks.contains_key(&(destination, *level)));
+        match reached {
+            Some(new_level) if destination != *grid_pos => {
+                transform.translation += (board.topology.to_world(&destination)
+                    - board.topology.to_world(&grid_pos))
+                .extend(0.0);
+                *grid_pos = destination;
+                *level = new_level;
+                sounds.send(SoundEffect::Footstep);
+            }
+            Some(_) => (),
+            None => warn!(
+                "The bot of seat {} can't walk to ({}, {})",
+                player.id + 1,
+                destination.x_pos,
+                destination.y_pos
+            ),
+        }
+    }
+}
+
+fn end_bot_turn(
+    mut bots: ResMut<ExternalBots>,
+    mut game_state: ResMut<GamePhase>,
+    treasure_lists: Res<TreasureLists>,
+    game_settings: Res<GameSettings>,
+    mut sounds: EventWriter<SoundEffect>,
+) {
+    if bots.step != BotStep::Done {
+        return;
+    }
+    bots.step = BotStep::Idle;
+    // Unless collecting a treasure already ended it
+    if bots.drives(game_state.current_player_id)
+        && !game_state.tile_push_phase
+        && !game_state.has_ended
+    {
+        end_turn(
+            &mut game_state,
+            &treasure_lists,
+            &game_settings,
+            &mut sounds,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_move() {
+        assert_eq!(
+            BotMove::parse("move 1 4 2 3"),
+            Some(BotMove {
+                clockwise_turns: 1,
+                slot: 4,
+                destination: GridPosition { x_pos: 2, y_pos: 3 },
+            })
+        );
+        // any spacing will do
+        assert_eq!(
+            BotMove::parse("  move\t0 0  0 0 "),
+            BotMove::parse("move 0 0 0 0")
+        );
+    }
+
+    #[test]
+    fn turns_wrap_around() {
+        let parsed = |line| BotMove::parse(line).map(|bot_move| bot_move.clockwise_turns);
+        assert_eq!(parsed("move 13 0 0 0"), Some(1));
+        assert_eq!(parsed("move -1 0 0 0"), Some(11));
+    }
+
+    #[test]
+    fn rejects_other_lines() {
+        for line in [
+            "",
+            "ready",
+            "MOVE 1 4 2 3",
+            "move",
+            "move 1 4 2",
+            "move 1 four 2 3",
+            "move 1.5 4 2 3",
+            // slots are counted from 0
+            "move 1 -4 2 3",
+            "move 1 4 2 99999999999",
+        ] {
+            assert_eq!(BotMove::parse(line), None, "{line:?}");
+        }
+    }
+}
@@ -1,50 +1,216 @@
-use bevy::prelude::*;
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    window::{PrimaryWindow, WindowResized},
+};
 
 use crate::{
     board_selector::SelectedBoard,
     phases::GameState,
     tile::{TILE_SCALE, TILE_SIZE},
+    GridPosition,
 };
 
 // Used for z-ordering
 const CAMERA_LEVEL: f32 = 20.0;
+// Bounds of the camera scale (a bigger scale shows more of the world)
+const MIN_ZOOM: f32 = 0.3;
+const MAX_ZOOM: f32 = 8.0;
+// Scale change for one PageUp/PageDown press
+const KEY_ZOOM_STEP: f32 = 0.2;
+// Relative scale change for one mouse wheel line
+const WHEEL_ZOOM_FACTOR: f32 = 0.1;
+// Approximate number of pixels in a mouse wheel line, for touchpads
+const PIXELS_PER_LINE: f32 = 20.0;
+// Fraction of the window left empty around the board when fitting
+const FIT_MARGIN: f32 = 1.1;
 
 pub struct Camera2dPlugin;
 
 impl Plugin for Camera2dPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, spawn_camera)
-            .add_systems(Update, zoom_camera.run_if(in_state(GameState::Playing)));
+            .add_systems(Update, refit_camera)
+            .add_systems(
+                Update,
+                (zoom_camera, pan_camera).run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
-fn spawn_camera(mut commands: Commands, selected_board: Res<SelectedBoard>) {
-    let num_tiles_x = selected_board.board.tiles.shape()[1];
-    let num_tiles_y = selected_board.board.tiles.shape()[0];
+fn spawn_camera(
+    mut commands: Commands,
+    selected_board: Res<SelectedBoard>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let mut transform = Transform {
+        translation: Vec3::new(0.0, 0.0, CAMERA_LEVEL),
+        ..default()
+    };
+    if let Ok(window) = window_query.get_single() {
+        fit_to_board(&mut transform, &selected_board, window);
+    }
 
-    // spawn camera above middle of the board
     commands.spawn(Camera2dBundle {
-        transform: Transform {
-            translation: Vec3 {
-                x: (num_tiles_x - 1) as f32 * TILE_SCALE.x * TILE_SIZE.x / 2.0,
-                y: (num_tiles_y - 1) as f32 * TILE_SCALE.y * TILE_SIZE.y / 2.0,
-                z: CAMERA_LEVEL,
-            },
-            scale: Vec3::new(num_tiles_x as f32 / 2.0, num_tiles_x as f32 / 2.0, 1.0),
-            ..default()
-        },
+        transform,
         ..default()
     });
 }
 
-fn zoom_camera(mut camera_query: Query<&mut Transform, With<Camera2d>>, keys: Res<Input<KeyCode>>) {
+fn refit_camera(
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    selected_board: Res<SelectedBoard>,
+    mut resize_events: EventReader<WindowResized>,
+    keys: Res<Input<KeyCode>>,
+) {
+    // Fit the board on demand, on window resize, and when another board is selected
+    let resized = resize_events.read().count() > 0;
+    if !(resized || selected_board.is_changed() || keys.just_pressed(KeyCode::F)) {
+        return;
+    }
+
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    for mut camera_transform in &mut camera_query {
+        fit_to_board(&mut camera_transform, &selected_board, window);
+    }
+}
+
+fn fit_to_board(camera_transform: &mut Transform, selected_board: &SelectedBoard, window: &Window) {
+    // Center the camera on the board and zoom so that the board and its push slots are visible
+    let board = &selected_board.board;
+    let num_tiles_x = board.tiles.shape()[1] as i32;
+    let num_tiles_y = board.tiles.shape()[0] as i32;
+    let tile_width = TILE_SIZE.x * TILE_SCALE.x;
+    let tile_height = TILE_SIZE.y * TILE_SCALE.y;
+
+    // Bounds of the centers of the board cells, whatever their layout
+    let (mut min, mut max) = (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN));
+    for y_pos in 0..num_tiles_y {
+        for x_pos in 0..num_tiles_x {
+            let pos = GridPosition { x_pos, y_pos };
+            if board.contains(&pos) {
+                let world = board.topology.to_world(&pos);
+                min = min.min(world);
+                max = max.max(world);
+            }
+        }
+    }
+    // and of the spare tiles waiting beside it
+    for pos in board.spare_pool() {
+        let world = board.topology.to_world(&pos);
+        min = min.min(world);
+        max = max.max(world);
+    }
+    if min.x > max.x {
+        // no cells
+        (min, max) = (Vec2::ZERO, Vec2::ZERO);
+    }
+
+    camera_transform.translation.x = (min.x + max.x) / 2.0;
+    camera_transform.translation.y = (min.y + max.y) / 2.0;
+
+    // the push slots add one tile on each side of the board
+    let board_width = max.x - min.x + 3.0 * tile_width;
+    let board_height = max.y - min.y + 3.0 * tile_height;
+
+    let scale = if window.width() > 0.0 && window.height() > 0.0 {
+        (board_width / window.width()).max(board_height / window.height()) * FIT_MARGIN
+    } else {
+        num_tiles_x as f32 / 2.0
+    };
+    set_zoom(camera_transform, scale);
+}
+
+fn set_zoom(camera_transform: &mut Transform, scale: f32) {
+    let scale = scale.clamp(MIN_ZOOM, MAX_ZOOM);
+    camera_transform.scale = Vec3::new(scale, scale, 1.0);
+}
+
+fn zoom_camera(
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    keys: Res<Input<KeyCode>>,
+    mut wheel_events: EventReader<MouseWheel>,
+) {
     let mut camera_transform = camera_query
         .get_single_mut()
         .expect("More than one Camera2d!");
 
     if keys.just_pressed(KeyCode::PageUp) {
-        camera_transform.scale += Vec3::new(0.2, 0.2, 0.0);
+        let scale = camera_transform.scale.x + KEY_ZOOM_STEP;
+        set_zoom(&mut camera_transform, scale);
     } else if keys.just_pressed(KeyCode::PageDown) {
-        camera_transform.scale -= Vec3::new(0.2, 0.2, 0.0);
+        let scale = camera_transform.scale.x - KEY_ZOOM_STEP;
+        set_zoom(&mut camera_transform, scale);
+    }
+
+    // Number of wheel lines scrolled this frame (positive when scrolling up)
+    let mut scrolled = 0.0;
+    for event in wheel_events.read() {
+        scrolled += match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        };
+    }
+    if scrolled == 0.0 {
+        return;
     }
+
+    let old_scale = camera_transform.scale.x;
+    // scrolling up zooms in
+    set_zoom(
+        &mut camera_transform,
+        old_scale * (1.0 - WHEEL_ZOOM_FACTOR).powf(scrolled),
+    );
+    let new_scale = camera_transform.scale.x;
+
+    // Keep the world point under the cursor in place
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    if let Some(cursor) = window.cursor_position() {
+        // Offset of the cursor from the window center, with y pointing up
+        let offset = Vec2::new(
+            cursor.x - window.width() / 2.0,
+            window.height() / 2.0 - cursor.y,
+        );
+        camera_transform.translation += (offset * (old_scale - new_scale)).extend(0.0);
+    }
+}
+
+fn pan_camera(
+    mut camera_query: Query<(&Camera, &GlobalTransform, &mut Transform), With<Camera2d>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    buttons: Res<Input<MouseButton>>,
+    mut last_cursor: Local<Option<Vec2>>,
+) {
+    // Drag the view around while holding the right or middle mouse button
+    let dragging = buttons.pressed(MouseButton::Right) || buttons.pressed(MouseButton::Middle);
+    let cursor = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    let previous = std::mem::replace(&mut *last_cursor, cursor.filter(|_| dragging));
+    let (Some(previous), Some(cursor)) = (previous, cursor) else {
+        return;
+    };
+    if !dragging || previous == cursor {
+        return;
+    }
+
+    let (camera, camera_global, mut camera_transform) = camera_query
+        .get_single_mut()
+        .expect("More than one Camera2d!");
+    // The cursor positions are in logical pixels, mapped through the camera they stay under
+    // the grabbed point whatever the scale factor of the screen
+    let (Some(from), Some(to)) = (
+        camera.viewport_to_world_2d(camera_global, previous),
+        camera.viewport_to_world_2d(camera_global, cursor),
+    ) else {
+        return;
+    };
+    camera_transform.translation -= (to - from).extend(0.0);
 }
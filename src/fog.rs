@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+
+use crate::{
+    board_selector::SelectedBoard,
+    movement::{
+        collect_passages, get_reachable_positions, pos_is_external, BridgeLevel, TilePassagesQuery,
+    },
+    phases::GameState,
+    player::Player,
+    theme::ChosenTheme,
+    tile::TileType,
+    treasure::Treasure,
+    GamePhase, GameSettings, GridPosition,
+};
+
+// How many steps away from the current player's reachable region tiles are revealed
+const FOG_RADIUS: i32 = 1;
+
+// The positions of the tiles the current player can see
+#[derive(Resource, Debug, Default)]
+struct RevealedTiles {
+    positions: Vec<GridPosition>,
+}
+
+// The back of a tile, drawn over it while it is hidden
+#[derive(Component, Debug)]
+struct TileBack;
+
+pub struct FogPlugin;
+
+impl Plugin for FogPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RevealedTiles::default()).add_systems(
+            Update,
+            (
+                add_tile_backs,
+                update_revealed_tiles,
+                (hide_tiles, hide_treasures),
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing))
+                .run_if(fog_is_on),
+        );
+    }
+}
+
+fn fog_is_on(game_settings: Res<GameSettings>) -> bool {
+    game_settings.fog_of_war
+}
+
+fn add_tile_backs(
+    mut commands: Commands,
+    tiles_query: Query<(Entity, &TileType), Added<TileType>>,
+    asset_server: Res<AssetServer>,
+    theme: Res<ChosenTheme>,
+) {
+    for (tile, tile_type) in &tiles_query {
+        let texture = theme.get().tile_back(tile_type.topology());
+        commands.entity(tile).with_children(|tile| {
+            tile.spawn((
+                SpriteBundle {
+                    texture: asset_server.load(texture),
+                    // Over the tile, under the players and treasures
+                    transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                    ..default()
+                },
+                TileBack,
+            ));
+        });
+    }
+}
+
+fn update_revealed_tiles(
+    tiles_query: TilePassagesQuery,
+    player_query: Query<(&Player, &GridPosition, &BridgeLevel)>,
+    mut revealed: ResMut<RevealedTiles>,
+    game_state: Res<GamePhase>,
+    selected_board: Res<SelectedBoard>,
+) {
+    // Only the tiles around where the current player can go are shown
+    let tiles = collect_passages(&tiles_query);
+
+    let mut reachable = vec![];
+    for (player, grid_pos, level) in &player_query {
+        if player.id == game_state.current_player_id {
+            reachable = get_reachable_positions(*grid_pos, *level, &tiles, &selected_board.board);
+        }
+    }
+    revealed.positions = tiles
+        .keys()
+        .filter(|pos| {
+            game_state.has_ended
+                || pos_is_external(pos, &selected_board.board)
+                || reachable.iter().any(|reachable_pos| {
+                    selected_board.board.topology.distance(reachable_pos, pos) <= FOG_RADIUS
+                })
+        })
+        .copied()
+        .collect();
+}
+
+fn hide_tiles(
+    mut back_query: Query<(&Parent, &mut Visibility), With<TileBack>>,
+    tiles_query: Query<&GridPosition, With<TileType>>,
+    revealed: Res<RevealedTiles>,
+) {
+    for (parent, mut visibility) in &mut back_query {
+        if let Ok(grid_pos) = tiles_query.get(parent.get()) {
+            set_visibility(&mut visibility, !revealed.positions.contains(grid_pos));
+        }
+    }
+}
+
+fn hide_treasures(
+    mut treasure_query: Query<(&GridPosition, &mut Visibility), With<Treasure>>,
+    revealed: Res<RevealedTiles>,
+) {
+    // Treasures stay hidden until their tile is revealed
+    for (grid_pos, mut visibility) in &mut treasure_query {
+        set_visibility(&mut visibility, revealed.positions.contains(grid_pos));
+    }
+}
+
+fn set_visibility(visibility: &mut Mut<Visibility>, visible: bool) {
+    let new_visibility = if visible {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    if **visibility != new_visibility {
+        **visibility = new_visibility;
+    }
+}
@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+
+use crate::{
+    bots::ExternalBots,
+    phases::GameState,
+    theme::{ChosenTheme, ThemeColor},
+    ui::{ChosenLanguage, Language},
+    GamePhase, GameSettings,
+};
+
+const FONT_SIZE: f32 = 50.0;
+
+// Who the device was last handed to, in pass-and-play games
+#[derive(Resource, Debug, Default)]
+pub struct HandOver {
+    player_id: i32,
+    // whether that player has confirmed they are holding the device
+    pub confirmed: bool,
+}
+
+#[derive(Component, Debug)]
+struct HandOverScreen;
+
+pub struct HandOverPlugin;
+
+impl Plugin for HandOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HandOver::default())
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_hand_over, spawn_hand_over_screen),
+            )
+            .add_systems(
+                Update,
+                (detect_turn_change, update_hand_over_screen)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            // Confirm after the game systems ran, so that the confirming key press
+            // does not also push a tile
+            .add_systems(
+                PostUpdate,
+                confirm_hand_over.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), cleanup_hand_over_screen);
+    }
+}
+
+// Run condition for the systems reading the current player's inputs
+pub fn turn_is_handed_over(hand_over: Res<HandOver>) -> bool {
+    hand_over.confirmed
+}
+
+fn reset_hand_over(
+    mut hand_over: ResMut<HandOver>,
+    game_settings: Res<GameSettings>,
+    bots: Res<ExternalBots>,
+) {
+    // Bots don't need to be handed the device
+    *hand_over = HandOver {
+        player_id: 0,
+        confirmed: !game_settings.hot_seat || bots.drives(0),
+    };
+}
+
+fn detect_turn_change(
+    mut hand_over: ResMut<HandOver>,
+    game_state: Res<GamePhase>,
+    game_settings: Res<GameSettings>,
+    bots: Res<ExternalBots>,
+) {
+    // A new turn hides everything until the next player takes the device
+    if hand_over.player_id != game_state.current_player_id {
+        *hand_over = HandOver {
+            player_id: game_state.current_player_id,
+            confirmed: !game_settings.hot_seat
+                || game_state.has_ended
+                || bots.drives(game_state.current_player_id),
+        };
+    }
+}
+
+fn confirm_hand_over(
+    mut hand_over: ResMut<HandOver>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+) {
+    if !hand_over.confirmed
+        && (keys.just_pressed(KeyCode::Return) || buttons.just_pressed(MouseButton::Left))
+    {
+        hand_over.confirmed = true;
+    }
+}
+
+fn spawn_hand_over_screen(mut commands: Commands, theme: Res<ChosenTheme>) {
+    let colors = theme.get().colors;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: colors.overlay.into(),
+                // Drawn above the rest of the UI
+                z_index: ZIndex::Global(10),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            HandOverScreen,
+            ThemeColor::Overlay,
+        ))
+        .with_children(|screen| {
+            screen.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: FONT_SIZE,
+                        color: colors.text,
+                        ..default()
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+                ThemeColor::Text,
+            ));
+        });
+}
+
+fn update_hand_over_screen(
+    mut screen_query: Query<(&mut Visibility, &Children), With<HandOverScreen>>,
+    mut text_query: Query<&mut Text>,
+    hand_over: Res<HandOver>,
+    language: Res<ChosenLanguage>,
+) {
+    if !hand_over.is_changed() && !language.is_changed() {
+        return;
+    }
+
+    let player = hand_over.player_id + 1;
+    let message = match language.lang {
+        Language::French => format!("Passez a Joueur {player}\n\nEntree ou clic pour commencer"),
+        Language::English => format!("Pass to Player {player}\n\nReturn or click to start"),
+    };
+    for (mut visibility, children) in &mut screen_query {
+        *visibility = if hand_over.confirmed {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        for &child in children {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = message.clone();
+            }
+        }
+    }
+}
+
+fn cleanup_hand_over_screen(
+    mut commands: Commands,
+    screen_query: Query<Entity, With<HandOverScreen>>,
+) {
+    for entity in &screen_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
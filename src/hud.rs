@@ -0,0 +1,656 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    avatars::PlayerProfile,
+    handover::HandOver,
+    phases::GameState,
+    rng::GameSeed,
+    teams::{team_name, Teams},
+    theme::{ChosenTheme, ThemeColor},
+    treasure::{CollectedLists, TreasureLists, TreasureSprites},
+    ui::{ChosenLanguage, Language},
+    GamePhase, GameSettings,
+};
+
+const FONT_SIZE: f32 = 28.0;
+const AVATAR_SIZE: f32 = 48.0;
+const AVATAR_RING_WIDTH: f32 = 3.0;
+const TARGET_SIZE: f32 = 96.0;
+const DECK_CARD_SIZE: f32 = 48.0;
+const COLLECTED_ICON_SIZE: f32 = 64.0;
+
+#[derive(Component, Debug)]
+struct Hud;
+
+// The row of the panel showing a player
+#[derive(Component, Debug)]
+struct PlayerRow {
+    id: i32,
+}
+
+#[derive(Component, Debug)]
+struct CollectedText {
+    id: i32,
+}
+
+#[derive(Component, Debug)]
+struct PhaseText;
+
+// The current targets of the current player, shown on demand
+// Alone it is the next card of the deck, a team can collect any of its treasures
+#[derive(Component, Debug)]
+struct TargetPanel;
+
+#[derive(Component, Debug)]
+struct TargetImage {
+    index: usize,
+}
+
+#[derive(Component, Debug)]
+struct TargetName;
+
+// The treasures collected by each player, shown once the game is over
+#[derive(Component, Debug)]
+struct EndPanel;
+
+// A card of the remaining deck of the current player, shown on demand
+#[derive(Component, Debug)]
+struct DeckCard {
+    index: usize,
+}
+
+#[derive(Component, Debug)]
+struct DeckPanel;
+
+// Reveals the current target while pressed
+#[derive(Component, Debug)]
+struct RevealButton;
+
+// Which of the current player's cards are revealed this frame
+#[derive(Resource, Debug, Default)]
+struct CardReveal {
+    target: bool,
+    deck: bool,
+}
+
+// The treasures left to the team of the current player
+#[derive(SystemParam)]
+struct CurrentDeck<'w> {
+    treasure_lists: Res<'w, TreasureLists>,
+    game_settings: Res<'w, GameSettings>,
+    game_state: Res<'w, GamePhase>,
+}
+
+impl CurrentDeck<'_> {
+    // The next target is the last of the list
+    fn remaining(&self) -> Option<&Vec<i32>> {
+        let team = self
+            .game_settings
+            .teams
+            .team_of(self.game_state.current_player_id);
+        self.treasure_lists.lists.get(&team)
+    }
+
+    // The treasures that can be collected now, the next one first
+    fn targets(&self) -> Vec<i32> {
+        let remaining = self.remaining().map(Vec::as_slice).unwrap_or_default();
+        match self.game_settings.teams {
+            Teams::Solo => remaining.last().into_iter().copied().collect(),
+            Teams::Pairs { .. } | Teams::Coop => remaining.iter().rev().copied().collect(),
+        }
+    }
+}
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CardReveal::default())
+            .add_systems(OnEnter(GameState::Playing), spawn_hud)
+            .add_systems(
+                Update,
+                (
+                    update_player_rows,
+                    update_phase_text,
+                    spawn_end_panel,
+                    (
+                        read_reveal_inputs,
+                        (display_current_target, display_remaining_deck),
+                    )
+                        .chain(),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), cleanup_hud);
+    }
+}
+
+fn spawn_hud(
+    mut commands: Commands,
+    game_settings: Res<GameSettings>,
+    language: Res<ChosenLanguage>,
+    asset_server: Res<AssetServer>,
+    seed: Res<GameSeed>,
+    theme: Res<ChosenTheme>,
+    profile: Res<PlayerProfile>,
+) {
+    let colors = theme.get().colors;
+    let text_style = TextStyle {
+        font_size: FONT_SIZE,
+        color: colors.text,
+        ..default()
+    };
+    // a team deck holds the treasures of all of its members
+    let max_team_size = game_settings
+        .teams
+        .team_ids(game_settings.num_players)
+        .into_iter()
+        .map(|team| {
+            game_settings
+                .teams
+                .members(team, game_settings.num_players)
+                .len()
+        })
+        .max()
+        .unwrap_or(1);
+    let deck_size = game_settings.treasures_to_get as usize * max_team_size;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::VMax(0.5),
+                    top: Val::VMin(1.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: colors.panel.into(),
+                ..default()
+            },
+            Hud,
+            ThemeColor::Panel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                TextBundle::from_section("", text_style.clone()),
+                PhaseText,
+                ThemeColor::Text,
+            ));
+            let seed_text = match language.lang {
+                Language::French => format!("Graine: {}", seed.value),
+                Language::English => format!("Seed: {}", seed.value),
+            };
+            panel.spawn((
+                TextBundle::from_section(
+                    seed_text,
+                    TextStyle {
+                        font_size: FONT_SIZE * 0.7,
+                        ..text_style.clone()
+                    },
+                ),
+                ThemeColor::Text,
+            ));
+
+            for id in 0..game_settings.num_players {
+                let name = match language.lang {
+                    Language::French => format!("Joueur {}", id + 1),
+                    Language::English => format!("Player {}", id + 1),
+                };
+                panel
+                    .spawn((
+                        NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                column_gap: Val::Px(10.0),
+                                padding: UiRect::all(Val::Px(4.0)),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        PlayerRow { id },
+                    ))
+                    .with_children(|row| {
+                        let avatar = profile.avatar(id);
+                        row.spawn(ImageBundle {
+                            style: Style {
+                                width: Val::Px(AVATAR_SIZE),
+                                height: Val::Px(AVATAR_SIZE),
+                                border: UiRect::all(Val::Px(AVATAR_RING_WIDTH)),
+                                ..default()
+                            },
+                            image: asset_server.load(avatar.token_path()).into(),
+                            ..default()
+                        })
+                        .insert(BorderColor(avatar.ring_color()));
+                        // teammates share the color of their tokens
+                        match game_settings.teams {
+                            Teams::Solo | Teams::Coop => row.spawn((
+                                TextBundle::from_section(name, text_style.clone()),
+                                ThemeColor::Text,
+                            )),
+                            Teams::Pairs { .. } => row.spawn(TextBundle::from_section(
+                                name,
+                                TextStyle {
+                                    color: game_settings.teams.color(id),
+                                    ..text_style.clone()
+                                },
+                            )),
+                        };
+                        row.spawn((
+                            TextBundle::from_section("", text_style.clone()),
+                            CollectedText { id },
+                            ThemeColor::Text,
+                        ));
+                    });
+            }
+
+            let reveal_label = match language.lang {
+                Language::French => "Voir mon tresor (Espace, Tab: tous)",
+                Language::English => "See my treasure (Space, Tab: all)",
+            };
+            panel
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(4.0)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        background_color: colors.button.into(),
+                        ..default()
+                    },
+                    RevealButton,
+                    ThemeColor::Button,
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        TextBundle::from_section(
+                            reveal_label,
+                            TextStyle {
+                                font_size: FONT_SIZE * 0.7,
+                                ..text_style.clone()
+                            },
+                        ),
+                        ThemeColor::Text,
+                    ));
+                });
+
+            panel
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        },
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    TargetPanel,
+                ))
+                .with_children(|target| {
+                    target.spawn((
+                        TextBundle::from_section("", text_style.clone()),
+                        TargetName,
+                        ThemeColor::Text,
+                    ));
+                    let num_targets = match game_settings.teams {
+                        Teams::Solo => 1,
+                        Teams::Pairs { .. } | Teams::Coop => deck_size,
+                    };
+                    target
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_wrap: FlexWrap::Wrap,
+                                max_width: Val::Px(TARGET_SIZE * 4.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|images| {
+                            for index in 0..num_targets {
+                                images.spawn((
+                                    ImageBundle {
+                                        style: Style {
+                                            width: Val::Px(TARGET_SIZE),
+                                            height: Val::Px(TARGET_SIZE),
+                                            ..default()
+                                        },
+                                        ..default()
+                                    },
+                                    TargetImage { index },
+                                ));
+                            }
+                        });
+                });
+
+            panel
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_wrap: FlexWrap::Wrap,
+                            max_width: Val::Px(DECK_CARD_SIZE * 4.0),
+                            ..default()
+                        },
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    DeckPanel,
+                ))
+                .with_children(|deck| {
+                    for index in 0..deck_size {
+                        deck.spawn((
+                            ImageBundle {
+                                style: Style {
+                                    width: Val::Px(DECK_CARD_SIZE),
+                                    height: Val::Px(DECK_CARD_SIZE),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            DeckCard { index },
+                        ));
+                    }
+                });
+        });
+}
+
+fn update_player_rows(
+    mut row_query: Query<(&PlayerRow, &mut BackgroundColor)>,
+    mut collected_query: Query<(&CollectedText, &mut Text)>,
+    collected_lists: Res<CollectedLists>,
+    game_settings: Res<GameSettings>,
+    game_state: Res<GamePhase>,
+    theme: Res<ChosenTheme>,
+) {
+    for (row, mut background) in &mut row_query {
+        // highlight the player whose turn it is
+        *background = if row.id == game_state.current_player_id && !game_state.has_ended {
+            theme.get().colors.highlight.into()
+        } else {
+            Color::NONE.into()
+        };
+    }
+    for (collected, mut text) in &mut collected_query {
+        // the progress of the whole team, shared by its members
+        let teams = game_settings.teams;
+        let members = teams.members(teams.team_of(collected.id), game_settings.num_players);
+        let num_collected = members
+            .iter()
+            .filter_map(|id| collected_lists.lists.get(id))
+            .map(|list| list.len())
+            .sum::<usize>();
+        let num_to_get = game_settings.treasures_to_get as usize * members.len();
+        text.sections[0].value = format!("{num_collected}/{num_to_get}");
+    }
+}
+
+fn update_phase_text(
+    mut text_query: Query<&mut Text, With<PhaseText>>,
+    game_state: Res<GamePhase>,
+    treasure_lists: Res<TreasureLists>,
+    game_settings: Res<GameSettings>,
+    language: Res<ChosenLanguage>,
+) {
+    let player = game_state.current_player_id + 1;
+    // in teams, the game ends as soon as a team has emptied its deck
+    let winning_team = match game_settings.teams {
+        Teams::Solo | Teams::Coop => None,
+        Teams::Pairs { .. } => game_settings
+            .teams
+            .team_ids(game_settings.num_players)
+            .into_iter()
+            .find(|team| {
+                treasure_lists
+                    .lists
+                    .get(team)
+                    .is_some_and(|list| list.is_empty())
+            }),
+    };
+    let phase = match (
+        &language.lang,
+        game_state.has_ended,
+        game_state.tile_push_phase,
+    ) {
+        (Language::French, true, _) => match (winning_team, game_settings.teams) {
+            (Some(team), _) => format!("{} gagne!", team_name(&language, team)),
+            (None, Teams::Coop) => "Le minotaure est vaincu!".to_string(),
+            (None, _) => "Partie terminee".to_string(),
+        },
+        (Language::French, false, true) => format!("Joueur {player}: pousser une tuile"),
+        (Language::French, false, false) => format!("Joueur {player}: se deplacer"),
+        (Language::English, true, _) => match (winning_team, game_settings.teams) {
+            (Some(team), _) => format!("{} wins!", team_name(&language, team)),
+            (None, Teams::Coop) => "The minotaur is beaten!".to_string(),
+            (None, _) => "Game over".to_string(),
+        },
+        (Language::English, false, true) => format!("Player {player}: push a tile"),
+        (Language::English, false, false) => format!("Player {player}: move"),
+    };
+    for mut text in &mut text_query {
+        text.sections[0].value = phase.clone();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_end_panel(
+    mut commands: Commands,
+    end_panel_query: Query<(), With<EndPanel>>,
+    game_state: Res<GamePhase>,
+    game_settings: Res<GameSettings>,
+    collected_lists: Res<CollectedLists>,
+    sprite_paths: Res<TreasureSprites>,
+    language: Res<ChosenLanguage>,
+    asset_server: Res<AssetServer>,
+    theme: Res<ChosenTheme>,
+) {
+    if !game_state.has_ended || !end_panel_query.is_empty() {
+        return;
+    }
+    let colors = theme.get().colors;
+    let text_style = TextStyle {
+        font_size: FONT_SIZE,
+        color: colors.text,
+        ..default()
+    };
+    let name_style = TextStyle {
+        font_size: FONT_SIZE * 0.5,
+        ..text_style.clone()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::VMax(0.5),
+                    bottom: Val::VMin(1.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: colors.panel.into(),
+                ..default()
+            },
+            // cleaned up with the rest of the HUD
+            Hud,
+            EndPanel,
+            ThemeColor::Panel,
+        ))
+        .with_children(|panel| {
+            for id in 0..game_settings.num_players {
+                let name = match language.lang {
+                    Language::French => format!("Joueur {}:", id + 1),
+                    Language::English => format!("Player {}:", id + 1),
+                };
+                panel
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            TextBundle::from_section(name, text_style.clone()),
+                            ThemeColor::Text,
+                        ));
+                        let collected = collected_lists.lists.get(&id).cloned().unwrap_or_default();
+                        for treasure_id in collected {
+                            let Some(entry) = sprite_paths.entry(treasure_id) else {
+                                continue;
+                            };
+                            // each icon with the name of the treasure below it
+                            row.spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    max_width: Val::Px(COLLECTED_ICON_SIZE * 1.5),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|card| {
+                                card.spawn(ImageBundle {
+                                    style: Style {
+                                        width: Val::Px(COLLECTED_ICON_SIZE),
+                                        height: Val::Px(COLLECTED_ICON_SIZE),
+                                        ..default()
+                                    },
+                                    image: asset_server.load(&entry.file).into(),
+                                    ..default()
+                                });
+                                card.spawn((
+                                    TextBundle::from_section(
+                                        entry.name.get(&language),
+                                        name_style.clone(),
+                                    )
+                                    .with_text_alignment(TextAlignment::Center),
+                                    ThemeColor::Text,
+                                ));
+                            });
+                        }
+                    });
+            }
+        });
+}
+
+fn read_reveal_inputs(
+    mut reveal: ResMut<CardReveal>,
+    button_query: Query<&Interaction, With<RevealButton>>,
+    hand_over: Res<HandOver>,
+    keys: Res<Input<KeyCode>>,
+) {
+    // Only the player holding the device can reveal their cards, while holding a key or the button.
+    // Everything is hidden again as soon as the turn is over.
+    let button_held = button_query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    let deck = hand_over.confirmed && keys.pressed(KeyCode::Tab);
+    *reveal = CardReveal {
+        target: deck || (hand_over.confirmed && (keys.pressed(KeyCode::Space) || button_held)),
+        deck,
+    };
+}
+
+#[allow(clippy::too_many_arguments)]
+fn display_current_target(
+    mut panel_query: Query<&mut Visibility, With<TargetPanel>>,
+    mut image_query: Query<(&TargetImage, &mut UiImage, &mut Style)>,
+    mut name_query: Query<&mut Text, With<TargetName>>,
+    reveal: Res<CardReveal>,
+    current_deck: CurrentDeck,
+    sprite_paths: Res<TreasureSprites>,
+    asset_server: Res<AssetServer>,
+    language: Res<ChosenLanguage>,
+) {
+    let targets = current_deck.targets();
+
+    let panel_visibility = if reveal.target && !targets.is_empty() {
+        for (image, mut texture, mut style) in &mut image_query {
+            match targets.get(image.index) {
+                Some(&id) => {
+                    set_texture(&mut texture, &asset_server, &sprite_paths, id);
+                    style.display = Display::Flex;
+                }
+                None => style.display = Display::None,
+            }
+        }
+        let names = targets
+            .iter()
+            .map(|&id| sprite_paths.describe(id, &language).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let label = match language.lang {
+            Language::French => format!("Trouver: {names}"),
+            Language::English => format!("Find: {names}"),
+        };
+        for mut text in &mut name_query {
+            if text.sections[0].value != label {
+                text.sections[0].value = label.clone();
+            }
+        }
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for mut visibility in &mut panel_query {
+        *visibility = panel_visibility;
+    }
+}
+
+fn display_remaining_deck(
+    mut panel_query: Query<&mut Visibility, With<DeckPanel>>,
+    mut card_query: Query<(&DeckCard, &mut UiImage, &mut Style)>,
+    reveal: Res<CardReveal>,
+    current_deck: CurrentDeck,
+    sprite_paths: Res<TreasureSprites>,
+    asset_server: Res<AssetServer>,
+) {
+    if reveal.deck {
+        let remaining = current_deck.remaining().cloned().unwrap_or_default();
+        for (card, mut image, mut style) in &mut card_query {
+            match remaining.iter().rev().nth(card.index) {
+                Some(&id) => {
+                    set_texture(&mut image, &asset_server, &sprite_paths, id);
+                    style.display = Display::Flex;
+                }
+                None => style.display = Display::None,
+            }
+        }
+    }
+    for mut visibility in &mut panel_query {
+        *visibility = if reveal.deck {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn set_texture(
+    image: &mut Mut<UiImage>,
+    asset_server: &AssetServer,
+    sprite_paths: &TreasureSprites,
+    treasure_id: i32,
+) {
+    let Some(entry) = sprite_paths.entry(treasure_id) else {
+        return;
+    };
+    let texture: Handle<Image> = asset_server.load(&entry.file);
+    if image.texture != texture {
+        image.texture = texture;
+    }
+}
+
+fn cleanup_hud(mut commands: Commands, hud_query: Query<Entity, With<Hud>>) {
+    for entity in &hud_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
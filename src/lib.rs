@@ -0,0 +1,168 @@
+mod actors;
+mod avatars;
+mod background;
+mod board;
+mod board_selector;
+mod boards_repository;
+mod bots;
+mod camera;
+// the frame rate display, only built with `--features debug`
+#[cfg(feature = "debug")]
+mod debug;
+mod fog;
+mod handover;
+mod hud;
+mod menu;
+mod minotaur;
+mod movement;
+mod phases;
+mod player;
+mod powers;
+mod puzzle_catalogue;
+mod puzzles;
+mod rng;
+mod simulation;
+mod solver;
+mod sound;
+mod teams;
+mod theme;
+mod tile;
+mod tile_registry;
+mod topology;
+mod treasure;
+mod treasure_catalogue;
+mod ui;
+
+use bevy::{asset::AssetMetaCheck, prelude::*};
+
+use avatars::AvatarsPlugin;
+use board::BoardPlugin;
+use board_selector::BoardSelectorPlugin;
+use bots::BotsPlugin;
+use camera::Camera2dPlugin;
+use fog::FogPlugin;
+use handover::HandOverPlugin;
+use hud::HudPlugin;
+use menu::MenuPlugin;
+use minotaur::MinotaurPlugin;
+use movement::MovementPlugin;
+use phases::GamePhasePlugin;
+use player::PlayerPlugin;
+use powers::PowersPlugin;
+use puzzle_catalogue::PuzzleCataloguePlugin;
+use puzzles::PuzzlesPlugin;
+use rng::RngPlugin;
+use sound::SoundPlugin;
+use teams::{Teams, TeamsPlugin};
+use theme::ThemePlugin;
+use treasure::TreasurePlugin;
+use treasure_catalogue::TreasureCataloguePlugin;
+use ui::UIPlugin;
+
+// The command line tools, each run by its own binary without a window
+pub use puzzles::solve_from_args;
+pub use simulation::simulate_from_args;
+
+// Number of players
+const NUM_PLAYERS: i32 = 4;
+const TREASURES_TO_GET: i32 = 6;
+
+#[derive(Resource, Debug, Clone)]
+pub struct GameSettings {
+    num_players: i32,
+    treasures_to_get: i32,
+    // whether players share one device and must hide their cards from each other
+    hot_seat: bool,
+    // whether power cards are dealt during the game
+    power_cards: bool,
+    // whether the tiles far from the current player are hidden
+    fog_of_war: bool,
+    // who shares a treasure deck with whom
+    teams: Teams,
+}
+
+#[derive(Resource, Debug)]
+pub struct GamePhase {
+    // The id of the player whose turn it is
+    current_player_id: i32,
+    // whether we are in the board movement phase
+    // (or the player movement phase, if false)
+    tile_push_phase: bool,
+    // whether the game has ended
+    has_ended: bool,
+    // number of rounds played, a round ending when the turn goes back to the first players
+    round: u32,
+}
+
+// The position of a player, a tile or a treasure in tile units
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GridPosition {
+    x_pos: i32,
+    y_pos: i32,
+}
+
+pub fn run() {
+    let mut app = App::new();
+    app
+        //Built-ins
+        .insert_resource(AssetMetaCheck::Never)
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "Complexcity".to_string(),
+                canvas: Some("#bevy".to_owned()),
+                fit_canvas_to_parent: true,
+                // Let web shortcuts work (like f5)
+                prevent_default_event_handling: false,
+                ..default()
+            }),
+            ..default()
+        }))
+        // Custom Resources
+        .insert_resource(GameSettings {
+            num_players: NUM_PLAYERS,
+            treasures_to_get: TREASURES_TO_GET,
+            hot_seat: false,
+            power_cards: false,
+            fog_of_war: false,
+            teams: Teams::Solo,
+        })
+        .insert_resource(GamePhase {
+            current_player_id: 0,
+            tile_push_phase: true,
+            has_ended: false,
+            round: 0,
+        })
+        // Custom plugins
+        .add_plugins((
+            BoardPlugin,
+            BoardSelectorPlugin,
+            Camera2dPlugin,
+            FogPlugin,
+            GamePhasePlugin,
+            HandOverPlugin,
+            HudPlugin,
+            MenuPlugin,
+            MovementPlugin,
+            PlayerPlugin,
+            PowersPlugin,
+            RngPlugin,
+            SoundPlugin,
+        ))
+        // A tuple of plugins holds at most 15 of them
+        .add_plugins((
+            AvatarsPlugin,
+            BotsPlugin,
+            MinotaurPlugin,
+            PuzzleCataloguePlugin,
+            PuzzlesPlugin,
+            TeamsPlugin,
+            ThemePlugin,
+            TreasurePlugin,
+            TreasureCataloguePlugin,
+            UIPlugin,
+        ));
+    // Debug plugin
+    #[cfg(feature = "debug")]
+    app.add_plugins(debug::DebugPlugin);
+    app.run();
+}
@@ -0,0 +1,484 @@
+use bevy::{prelude::*, ui::RelativeCursorPosition, window::ReceivedCharacter};
+use rand::random;
+
+use crate::{
+    phases::GameState,
+    puzzles::{puzzle_menu_text, AvailablePuzzles, PuzzleMode, PuzzleScores},
+    rng::GameSeed,
+    sound::AudioSettings,
+    theme::{ChosenTheme, Palette},
+    ui::{ChosenLanguage, Language},
+    GameSettings,
+};
+
+const TITLE_SIZE: f32 = 80.0;
+const FONT_SIZE: f32 = 30.0;
+const SLIDER_WIDTH: f32 = 300.0;
+const SLIDER_HEIGHT: f32 = 24.0;
+const TRACK_COLOR: Color = Color::rgb(0.2, 0.2, 0.2);
+
+#[derive(Component, Debug)]
+struct MainMenu;
+
+// A volume slider, on the clickable track
+#[derive(Component, Debug, Clone, Copy)]
+enum VolumeSlider {
+    Music,
+    Effects,
+}
+
+// The filled part of a volume slider
+#[derive(Component, Debug)]
+struct SliderFill(VolumeSlider);
+
+#[derive(Component, Debug, Clone, Copy)]
+enum SettingsButton {
+    Mute,
+    Language,
+    HotSeat,
+    PowerCards,
+    FogOfWar,
+    Teams,
+    Theme,
+}
+
+// The text of a button showing the current value of its setting
+#[derive(Component, Debug)]
+struct SettingText(SettingsButton);
+
+#[derive(Component, Debug)]
+struct SeedText;
+
+#[derive(Component, Debug)]
+struct PuzzleText;
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::MainMenu), spawn_main_menu)
+            .add_systems(OnExit(GameState::MainMenu), cleanup_main_menu)
+            .add_systems(
+                Update,
+                (
+                    drag_sliders,
+                    press_settings_buttons,
+                    update_settings_display,
+                    (edit_seed, update_seed_text).chain(),
+                    update_puzzle_text,
+                    respawn_on_display_change,
+                )
+                    .run_if(in_state(GameState::MainMenu)),
+            );
+    }
+}
+
+fn spawn_main_menu(
+    mut commands: Commands,
+    language: Res<ChosenLanguage>,
+    audio_settings: Res<AudioSettings>,
+    theme: Res<ChosenTheme>,
+) {
+    build_main_menu(
+        &mut commands,
+        &language,
+        &audio_settings,
+        &theme.get().colors,
+    );
+}
+
+fn build_main_menu(
+    commands: &mut Commands,
+    language: &ChosenLanguage,
+    audio_settings: &AudioSettings,
+    colors: &Palette,
+) {
+    let text_style = TextStyle {
+        font_size: FONT_SIZE,
+        color: colors.text,
+        ..default()
+    };
+
+    let (start_text, music_text, effects_text, language_text) = match language.lang {
+        Language::French => (
+            "Entree: Jouer\nB: Changer de plateau\nM: Couper le son",
+            "Musique",
+            "Effets",
+            "Langue: Francais",
+        ),
+        Language::English => (
+            "Return: Play\nB: Change board\nM: Mute",
+            "Music",
+            "Effects",
+            "Language: English",
+        ),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+                ..default()
+            },
+            MainMenu,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Labby",
+                TextStyle {
+                    font_size: TITLE_SIZE,
+                    ..text_style.clone()
+                },
+            ));
+            parent.spawn(
+                TextBundle::from_section(start_text, text_style.clone())
+                    .with_text_alignment(TextAlignment::Center),
+            );
+            parent.spawn((
+                TextBundle::from_section("", text_style.clone())
+                    .with_text_alignment(TextAlignment::Center),
+                SeedText,
+            ));
+            parent.spawn((
+                TextBundle::from_section("", text_style.clone())
+                    .with_text_alignment(TextAlignment::Center),
+                PuzzleText,
+            ));
+            spawn_slider(
+                parent,
+                music_text,
+                VolumeSlider::Music,
+                audio_settings.music_volume,
+                &text_style,
+            );
+            spawn_slider(
+                parent,
+                effects_text,
+                VolumeSlider::Effects,
+                audio_settings.effects_volume,
+                &text_style,
+            );
+            for button in [
+                SettingsButton::Mute,
+                SettingsButton::HotSeat,
+                SettingsButton::PowerCards,
+                SettingsButton::FogOfWar,
+                SettingsButton::Teams,
+                SettingsButton::Theme,
+            ] {
+                spawn_button(
+                    parent,
+                    button,
+                    "",
+                    &text_style,
+                    colors.button,
+                    SettingText(button),
+                );
+            }
+            spawn_button(
+                parent,
+                SettingsButton::Language,
+                language_text,
+                &text_style,
+                colors.button,
+                (),
+            );
+        });
+}
+
+fn spawn_slider(
+    parent: &mut ChildBuilder,
+    label: &str,
+    slider: VolumeSlider,
+    value: f32,
+    text_style: &TextStyle,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(12.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn(TextBundle::from_section(label, text_style.clone()));
+            row.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(SLIDER_WIDTH),
+                        height: Val::Px(SLIDER_HEIGHT),
+                        ..default()
+                    },
+                    background_color: TRACK_COLOR.into(),
+                    ..default()
+                },
+                Interaction::default(),
+                RelativeCursorPosition::default(),
+                slider,
+            ))
+            .with_children(|track| {
+                track.spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(value * 100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: text_style.color.into(),
+                        ..default()
+                    },
+                    SliderFill(slider),
+                ));
+            });
+        });
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    button: SettingsButton,
+    label: &str,
+    text_style: &TextStyle,
+    color: Color,
+    text_marker: impl Bundle,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::axes(Val::Px(16.0), Val::Px(6.0)),
+                    ..default()
+                },
+                background_color: color.into(),
+                ..default()
+            },
+            button,
+        ))
+        .with_children(|button| {
+            button.spawn((
+                TextBundle::from_section(label, text_style.clone()),
+                text_marker,
+            ));
+        });
+}
+
+fn drag_sliders(
+    slider_query: Query<(&Interaction, &RelativeCursorPosition, &VolumeSlider)>,
+    mut audio_settings: ResMut<AudioSettings>,
+) {
+    for (interaction, cursor, slider) in &slider_query {
+        if let (Interaction::Pressed, Some(position)) = (interaction, cursor.normalized) {
+            let value = position.x.clamp(0.0, 1.0);
+            match slider {
+                VolumeSlider::Music => audio_settings.music_volume = value,
+                VolumeSlider::Effects => audio_settings.effects_volume = value,
+            }
+        }
+    }
+}
+
+fn press_settings_buttons(
+    button_query: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+    mut audio_settings: ResMut<AudioSettings>,
+    mut language: ResMut<ChosenLanguage>,
+    mut game_settings: ResMut<GameSettings>,
+    mut theme: ResMut<ChosenTheme>,
+) {
+    for (interaction, button) in &button_query {
+        if *interaction == Interaction::Pressed {
+            match button {
+                SettingsButton::Mute => audio_settings.muted = !audio_settings.muted,
+                SettingsButton::HotSeat => game_settings.hot_seat = !game_settings.hot_seat,
+                SettingsButton::PowerCards => {
+                    game_settings.power_cards = !game_settings.power_cards
+                }
+                SettingsButton::FogOfWar => game_settings.fog_of_war = !game_settings.fog_of_war,
+                SettingsButton::Teams => {
+                    game_settings.teams = game_settings.teams.next(game_settings.num_players)
+                }
+                SettingsButton::Theme => theme.next(),
+                SettingsButton::Language => {
+                    language.lang = match language.lang {
+                        Language::French => Language::English,
+                        Language::English => Language::French,
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn update_settings_display(
+    mut fill_query: Query<(&mut Style, &SliderFill)>,
+    mut text_query: Query<(&mut Text, &SettingText)>,
+    audio_settings: Res<AudioSettings>,
+    game_settings: Res<GameSettings>,
+    language: Res<ChosenLanguage>,
+    theme: Res<ChosenTheme>,
+) {
+    for (mut style, fill) in &mut fill_query {
+        let value = match fill.0 {
+            VolumeSlider::Music => audio_settings.music_volume,
+            VolumeSlider::Effects => audio_settings.effects_volume,
+        };
+        style.width = Val::Percent(value * 100.0);
+    }
+    for (mut text, setting) in &mut text_query {
+        if let SettingsButton::Theme = setting.0 {
+            // the same word in both languages
+            text.sections[0].value = format!("Theme: {}", theme.get().name.get(&language));
+            continue;
+        }
+        if let SettingsButton::Teams = setting.0 {
+            // not a yes/no setting
+            let label = match language.lang {
+                Language::French => "Equipes",
+                Language::English => "Teams",
+            };
+            let value = game_settings
+                .teams
+                .label(&language.lang, game_settings.num_players);
+            text.sections[0].value = format!("{label}: {value}");
+            continue;
+        }
+        let (label, value) = match (&language.lang, setting.0) {
+            (Language::French, SettingsButton::Mute) => ("Son", !audio_settings.muted),
+            (Language::English, SettingsButton::Mute) => ("Sound", !audio_settings.muted),
+            (Language::French, SettingsButton::HotSeat) => {
+                ("Un seul appareil", game_settings.hot_seat)
+            }
+            (Language::English, SettingsButton::HotSeat) => {
+                ("Pass and play", game_settings.hot_seat)
+            }
+            (Language::French, SettingsButton::PowerCards) => {
+                ("Cartes pouvoir", game_settings.power_cards)
+            }
+            (Language::English, SettingsButton::PowerCards) => {
+                ("Power cards", game_settings.power_cards)
+            }
+            (Language::French, SettingsButton::FogOfWar) => {
+                ("Brouillard", game_settings.fog_of_war)
+            }
+            (Language::English, SettingsButton::FogOfWar) => {
+                ("Fog of war", game_settings.fog_of_war)
+            }
+            (_, SettingsButton::Language | SettingsButton::Teams | SettingsButton::Theme) => {
+                continue
+            }
+        };
+        let value = match (&language.lang, setting.0, value) {
+            (Language::French, SettingsButton::Mute, true) => "Actif",
+            (Language::French, SettingsButton::Mute, false) => "Coupe",
+            (Language::French, _, true) => "Oui",
+            (Language::French, _, false) => "Non",
+            (Language::English, SettingsButton::Mute, false) => "Muted",
+            (Language::English, _, true) => "On",
+            (Language::English, _, false) => "Off",
+        };
+        text.sections[0].value = format!("{label}: {value}");
+    }
+}
+
+fn edit_seed(
+    mut seed: ResMut<GameSeed>,
+    mut characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+) {
+    // Type digits to choose the seed of the next games, N to go back to random seeds
+    for character in characters.read() {
+        if let Some(digit) = character.char.to_digit(10) {
+            if seed.chosen {
+                if let Some(value) = seed
+                    .value
+                    .checked_mul(10)
+                    .and_then(|value| value.checked_add(digit.into()))
+                {
+                    seed.value = value;
+                }
+            } else {
+                // start a new seed instead of appending to the random one
+                seed.value = digit.into();
+                seed.chosen = true;
+            }
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) && seed.chosen {
+        seed.value /= 10;
+    }
+    if keys.just_pressed(KeyCode::N) {
+        seed.value = random();
+        seed.chosen = false;
+    }
+}
+
+fn update_seed_text(
+    mut text_query: Query<&mut Text, With<SeedText>>,
+    seed: Res<GameSeed>,
+    language: Res<ChosenLanguage>,
+) {
+    for mut text in &mut text_query {
+        text.sections[0].value = match (&language.lang, seed.chosen) {
+            (Language::French, true) => {
+                format!("Graine: {}\n(chiffres: saisir, N: aleatoire)", seed.value)
+            }
+            (Language::French, false) => {
+                format!("Graine aleatoire: {}\n(chiffres: saisir)", seed.value)
+            }
+            (Language::English, true) => format!("Seed: {}\n(digits: type, N: random)", seed.value),
+            (Language::English, false) => format!("Random seed: {}\n(digits: type)", seed.value),
+        };
+    }
+}
+
+fn update_puzzle_text(
+    mut text_query: Query<&mut Text, With<PuzzleText>>,
+    mode: Res<PuzzleMode>,
+    available: Res<AvailablePuzzles>,
+    scores: Res<PuzzleScores>,
+    language: Res<ChosenLanguage>,
+) {
+    let value = puzzle_menu_text(&mode, &available, &scores, &language.lang);
+    for mut text in &mut text_query {
+        // not to lay the text out again on every frame
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+fn respawn_on_display_change(
+    mut commands: Commands,
+    menu_query: Query<Entity, With<MainMenu>>,
+    language: Res<ChosenLanguage>,
+    audio_settings: Res<AudioSettings>,
+    theme: Res<ChosenTheme>,
+) {
+    // Rebuild the menu to translate all of its texts, or to recolor it
+    if (language.is_changed() && !language.is_added()) || (theme.is_changed() && !theme.is_added())
+    {
+        for entity in &menu_query {
+            commands.entity(entity).despawn_recursive();
+        }
+        build_main_menu(
+            &mut commands,
+            &language,
+            &audio_settings,
+            &theme.get().colors,
+        );
+    }
+}
+
+fn cleanup_main_menu(mut commands: Commands, menu_query: Query<Entity, With<MainMenu>>) {
+    for entity in &menu_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
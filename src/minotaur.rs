@@ -0,0 +1,198 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    board::Board,
+    board_selector::SelectedBoard,
+    movement::{collect_passages, get_next_steps, BridgeLevel, TilePassages, TilePassagesQuery},
+    phases::GameState,
+    player::Player,
+    sound::SoundEffect,
+    teams::Teams,
+    tile::TileType,
+    GamePhase, GameSettings, GridPosition,
+};
+
+const MINOTAUR_SCALE: Vec3 = Vec3::new(0.4, 0.4, 0.0);
+// How many tiles the minotaur walks after each player turn
+const MINOTAUR_STEPS: usize = 2;
+
+// The token chasing the players in the co-op mode
+// Pushes move it like a player, so walls can trap it or free it
+#[derive(Component, Debug)]
+struct Minotaur {
+    // The (round, player) of the last turn it moved after
+    last_turn: (u32, i32),
+}
+
+type PlayersQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Player,
+        &'static mut GridPosition,
+        &'static mut Transform,
+    ),
+    Without<Minotaur>,
+>;
+
+type MinotaurQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Minotaur,
+        &'static mut GridPosition,
+        &'static mut Transform,
+        &'static mut BridgeLevel,
+    ),
+    Without<TileType>,
+>;
+
+pub struct MinotaurPlugin;
+
+impl Plugin for MinotaurPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            spawn_minotaur.run_if(minotaur_is_on),
+        )
+        .add_systems(
+            Update,
+            move_minotaur
+                .run_if(in_state(GameState::Playing))
+                .run_if(minotaur_is_on),
+        )
+        .add_systems(OnExit(GameState::Playing), cleanup_minotaur);
+    }
+}
+
+fn minotaur_is_on(game_settings: Res<GameSettings>) -> bool {
+    game_settings.teams == Teams::Coop
+}
+
+fn spawn_minotaur(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    selected_board: Res<SelectedBoard>,
+    game_state: Res<GamePhase>,
+) {
+    // The minotaur starts in the middle of the maze, or the closest tile to it
+    let board = &selected_board.board;
+    let (max_x, max_y) = board.max_coords();
+    let middle = GridPosition {
+        x_pos: max_x / 2,
+        y_pos: max_y / 2,
+    };
+    let Some(start) = (0..=max_y)
+        .flat_map(|y_pos| (0..=max_x).map(move |x_pos| GridPosition { x_pos, y_pos }))
+        .filter(|pos| board.contains(pos))
+        .min_by_key(|pos| board.topology.distance(pos, &middle))
+    else {
+        return;
+    };
+
+    commands.spawn((
+        Minotaur {
+            last_turn: (game_state.round, game_state.current_player_id),
+        },
+        start,
+        BridgeLevel::Over,
+        SpriteBundle {
+            texture: asset_server.load("creatures/minotaur.png"),
+            transform: Transform {
+                translation: board.topology.to_world(&start).extend(1.0),
+                scale: MINOTAUR_SCALE,
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+fn move_minotaur(
+    mut minotaur_query: MinotaurQuery,
+    mut player_query: PlayersQuery,
+    tiles_query: TilePassagesQuery,
+    game_state: Res<GamePhase>,
+    selected_board: Res<SelectedBoard>,
+    mut sounds: EventWriter<SoundEffect>,
+) {
+    let board = &selected_board.board;
+    let turn = (game_state.round, game_state.current_player_id);
+    for (mut minotaur, mut grid_pos, mut transform, mut level) in &mut minotaur_query {
+        if minotaur.last_turn == turn || game_state.has_ended {
+            continue;
+        }
+        // A player has just ended their turn
+        minotaur.last_turn = turn;
+
+        let tiles = collect_passages(&tiles_query);
+        let targets = player_query
+            .iter()
+            .map(|(_, pos, _)| *pos)
+            .collect::<Vec<_>>();
+        let Some(path) = path_to_nearest(*grid_pos, *level, &targets, &tiles, board) else {
+            // walled in, or the players are out of reach
+            continue;
+        };
+        let Some(&(new_pos, new_level)) = path.iter().take(MINOTAUR_STEPS).next_back() else {
+            continue;
+        };
+        transform.translation +=
+            (board.topology.to_world(&new_pos) - board.topology.to_world(&grid_pos)).extend(0.0);
+        *grid_pos = new_pos;
+        *level = new_level;
+
+        // The players it reaches go back to where they started
+        for (player, mut player_pos, mut player_transform) in &mut player_query {
+            if *player_pos == new_pos {
+                player_transform.translation += (board.topology.to_world(&player.spawn)
+                    - board.topology.to_world(&player_pos))
+                .extend(0.0);
+                *player_pos = player.spawn;
+                sounds.send(SoundEffect::MinotaurCatch);
+            }
+        }
+    }
+}
+
+fn path_to_nearest(
+    start: GridPosition,
+    start_level: BridgeLevel,
+    targets: &[GridPosition],
+    tiles: &HashMap<GridPosition, TilePassages>,
+    board: &Board,
+) -> Option<Vec<(GridPosition, BridgeLevel)>> {
+    // The shortest walk (without start) to one of the targets, through the open passages
+    let mut previous = HashMap::default();
+    previous.insert((start, start_level), None);
+    let mut to_visit = VecDeque::from([(start, start_level)]);
+
+    while let Some((pos, level)) = to_visit.pop_front() {
+        if targets.contains(&pos) && pos != start {
+            // walk back to the start
+            let mut path = vec![(pos, level)];
+            while let Some(Some(step)) = previous.get(path.last()?) {
+                path.push(*step);
+            }
+            path.pop();
+            path.reverse();
+            return Some(path);
+        }
+
+        for step in get_next_steps(pos, level, tiles, board) {
+            if !previous.contains_key(&step) {
+                previous.insert(step, Some((pos, level)));
+                to_visit.push_back(step);
+            }
+        }
+    }
+    None
+}
+
+fn cleanup_minotaur(mut commands: Commands, minotaur_query: Query<Entity, With<Minotaur>>) {
+    for entity in &minotaur_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,12 +1,19 @@
-use std::f32::consts::PI;
-
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 
 use crate::{
+    board::{Board, PushSlot},
     board_selector::SelectedBoard,
+    bots::human_is_playing,
+    handover::turn_is_handed_over,
     phases::GameState,
     player::Player,
-    tile::{rotate_ways, OpenWays, TileType, TILE_SCALE, TILE_SIZE},
+    powers::{step_through_wall, BlockedSlot, PowerCards},
+    sound::SoundEffect,
+    theme::ChosenTheme,
+    tile::{EntryWays, OpenWays, Orientation, TileType, UnderWays, TILE_SCALE, TILE_SIZE},
+    tile_registry::TileBehaviour,
+    topology::Topology,
+    ui::Language,
     GamePhase, GridPosition,
 };
 
@@ -18,18 +25,226 @@ pub enum CanMove {
 }
 
 // The allowed movement directions
-#[derive(Debug)]
+// Up and Down are only used on square boards, the diagonals only on hex boards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     Up,
     Down,
     Left,
     Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
 }
 
+impl Direction {
+    // One step in this direction, as (x, y)
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Direction::Up => (0, 1),
+            Direction::Down => (0, -1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            // in the axial coordinates of hex boards
+            Direction::UpLeft => (-1, 1),
+            Direction::UpRight => (0, 1),
+            Direction::DownLeft => (0, -1),
+            Direction::DownRight => (1, -1),
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::UpLeft => Direction::DownRight,
+            Direction::UpRight => Direction::DownLeft,
+            Direction::DownLeft => Direction::UpRight,
+            Direction::DownRight => Direction::UpLeft,
+        }
+    }
+
+    pub fn name(self, language: &Language) -> &'static str {
+        match (language, self) {
+            (Language::French, Direction::Up) => "le haut",
+            (Language::French, Direction::Down) => "le bas",
+            (Language::French, Direction::Left) => "la gauche",
+            (Language::French, Direction::Right) => "la droite",
+            (Language::French, Direction::UpLeft) => "le haut a gauche",
+            (Language::French, Direction::UpRight) => "le haut a droite",
+            (Language::French, Direction::DownLeft) => "le bas a gauche",
+            (Language::French, Direction::DownRight) => "le bas a droite",
+            (Language::English, Direction::Up) => "up",
+            (Language::English, Direction::Down) => "down",
+            (Language::English, Direction::Left) => "left",
+            (Language::English, Direction::Right) => "right",
+            (Language::English, Direction::UpLeft) => "up and left",
+            (Language::English, Direction::UpRight) => "up and right",
+            (Language::English, Direction::DownLeft) => "down and left",
+            (Language::English, Direction::DownRight) => "down and right",
+        }
+    }
+}
+
+// Which passage of a bridge tile a player is in
+// Always Over when not on a bridge
+#[derive(Debug, Default, Component, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BridgeLevel {
+    #[default]
+    Over,
+    Under,
+}
+
+// What the movement rules need to know about a tile
+#[derive(Debug, Clone, Copy)]
+pub struct TilePassages {
+    pub tile_type: TileType,
+    pub open_ways: OpenWays,
+    pub entry_ways: EntryWays,
+    pub under_ways: UnderWays,
+}
+
+pub type TilePassagesQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GridPosition,
+        &'static TileType,
+        &'static OpenWays,
+        &'static EntryWays,
+        &'static UnderWays,
+    ),
+    Without<Player>,
+>;
+
+// The tiles and the treasures on them
+pub type SpareStacksQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut GridPosition,
+        &'static mut Transform,
+        Option<&'static mut Orientation>,
+    ),
+    Without<Player>,
+>;
+
+// Everything a push can move
+pub type PushedEntitiesQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        &'static mut GridPosition,
+        Option<&'static mut BridgeLevel>,
+    ),
+>;
+
+// The indices (in PushSlots) of the slots that can't be pushed from
 #[derive(Resource, Debug, Default)]
-struct IllegalPushPositions {
+pub struct IllegalPushPositions {
     fresh_pos_on_top: bool,
-    positions: Vec<GridPosition>,
+    positions: Vec<usize>,
+    // slots blocked by a power card
+    pub blocked: Vec<BlockedSlot>,
+}
+
+impl IllegalPushPositions {
+    pub fn is_illegal(&self, slot: usize) -> bool {
+        self.positions.contains(&slot) || self.blocked.iter().any(|blocked| blocked.slot == slot)
+    }
+}
+
+// The places around the board the spare tile can be pushed from
+#[derive(Resource, Debug, Default)]
+pub struct PushSlots {
+    pub slots: Vec<PushSlot>,
+    // The slot of the spare tile
+    pub current: usize,
+    pub topology: Topology,
+}
+
+impl PushSlots {
+    // Where a slot is shown in the world: its cell, towards the tiles it pushes by `shift` tiles
+    fn slot_point(&self, slot: &PushSlot, shift: f32) -> Vec2 {
+        let (step_x, step_y) = slot.dir.offset();
+        let next = GridPosition {
+            x_pos: slot.pos.x_pos + step_x,
+            y_pos: slot.pos.y_pos + step_y,
+        };
+        let center = self.topology.to_world(&slot.pos);
+        center + (self.topology.to_world(&next) - center) * shift
+    }
+
+    // The closest slot in a screen direction from the current one, preferring those in line with it
+    fn next_slot(&self, screen_dir: Vec2) -> Option<usize> {
+        let tile_width = TILE_SIZE.x * TILE_SCALE.x;
+        // Slots sharing a position are told apart by the side they push to
+        let from = self.slot_point(self.slots.get(self.current)?, -0.3);
+
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let offset = (self.slot_point(slot, -0.3) - from) / tile_width;
+                let along = offset.dot(screen_dir);
+                let across = offset.perp_dot(screen_dir).abs();
+                (along > 0.01).then_some((index, across >= 0.5, along + across))
+            })
+            .min_by(|a, b| (a.1, a.2).partial_cmp(&(b.1, b.2)).unwrap())
+            .map(|(index, ..)| index)
+    }
+
+    // Push the line of the current slot, returns the slot of the opposite push
+    fn push(&mut self, entities_query: &mut PushedEntitiesQuery) -> Option<usize> {
+        let topology = self.topology;
+        let slot = self.slots.get(self.current)?.clone();
+        let first = slot.line[0];
+        let last = *slot.line.last()?;
+
+        for (mut transform, mut grid_pos, level) in entities_query.iter_mut() {
+            let new_pos = if *grid_pos == slot.pos {
+                // The spare tile and what is on it enter the board
+                first
+            } else if *grid_pos == last {
+                if let Some(mut level) = level {
+                    // Players pushed out come back at the other end of the line
+                    *level = BridgeLevel::Over;
+                    first
+                } else {
+                    slot.exit
+                }
+            } else if let Some(index) = slot.line.iter().position(|pos| pos == &*grid_pos) {
+                slot.line[index + 1]
+            } else {
+                continue;
+            };
+            *grid_pos = new_pos;
+            let world = topology.to_world(&new_pos);
+            transform.translation.x = world.x;
+            transform.translation.y = world.y;
+        }
+
+        // The tile pushed out is now the spare, in the opposite slot
+        let opposite = self
+            .slots
+            .iter()
+            .position(|other| other.pos == slot.exit && other.dir == slot.dir.opposite());
+        if let Some(opposite) = opposite {
+            self.current = opposite;
+        }
+        opposite
+    }
+}
+
+// Shows a push slot, pointing to the tiles it pushes
+// Not a GridPosition, so that pushes don't move it
+#[derive(Component, Debug)]
+struct PushSlotMarker {
+    index: usize,
 }
 
 pub struct MovementPlugin;
@@ -39,478 +254,466 @@ impl Plugin for MovementPlugin {
         app.insert_resource(IllegalPushPositions {
             ..Default::default()
         })
-        .add_systems(OnEnter(GameState::Playing), compute_illegal_pushes)
+        .insert_resource(PushSlots::default())
+        .add_systems(OnEnter(GameState::Playing), setup_push_slots)
         .add_systems(
             Update,
-            (
-                move_current_player,
-                move_current_tile,
-                trigger_push,
-                warp_player,
-            )
-                .run_if(in_state(GameState::Playing)),
-        );
+            (move_current_player, move_current_tile, trigger_push)
+                .run_if(in_state(GameState::Playing))
+                .run_if(turn_is_handed_over)
+                .run_if(human_is_playing),
+        )
+        .add_systems(
+            Update,
+            (highlight_reachable_tiles, highlight_push_slots).run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(OnExit(GameState::Playing), cleanup_push_slots);
     }
 }
 
 fn move_current_player(
-    mut player_query: Query<(&mut GridPosition, &mut Transform, &Player, &CanMove)>,
-    tiles_query: Query<(&OpenWays, &GridPosition), Without<Player>>,
+    mut player_query: Query<(
+        &mut GridPosition,
+        &mut Transform,
+        &mut BridgeLevel,
+        &Player,
+        &CanMove,
+    )>,
+    tiles_query: TilePassagesQuery,
     game_state: Res<GamePhase>,
     keys: Res<Input<KeyCode>>,
     selected_board: Res<SelectedBoard>,
+    mut sounds: EventWriter<SoundEffect>,
+    mut power_cards: ResMut<PowerCards>,
 ) {
+    let board = &selected_board.board;
     if !game_state.has_ended {
         // Nobody can move if game has ended
-        for (mut grid_pos, mut transform, player, can_move) in &mut player_query {
+        for (mut grid_pos, mut transform, mut level, player, can_move) in &mut player_query {
             if (player.id == game_state.current_player_id)
                 && (!game_state.tile_push_phase)
                 && (matches!(can_move, &CanMove::Yes))
             {
                 // If this player can move, it is their turn and they're not pushing tiles
-                if keys.just_pressed(KeyCode::Right)
-                    && player_move_ok(*grid_pos, Direction::Right, &tiles_query, &selected_board)
-                {
-                    // if right arrow was pressed and this movement is legal, move the player
-                    grid_pos.x_pos += 1;
-                    transform.translation.x += TILE_SIZE.x * TILE_SCALE.x
-                } else if keys.just_pressed(KeyCode::Left)
-                    && player_move_ok(*grid_pos, Direction::Left, &tiles_query, &selected_board)
-                {
-                    grid_pos.x_pos -= 1;
-                    transform.translation.x -= TILE_SIZE.x * TILE_SCALE.x
-                } else if keys.just_pressed(KeyCode::Up)
-                    && player_move_ok(*grid_pos, Direction::Up, &tiles_query, &selected_board)
-                {
-                    grid_pos.y_pos += 1;
-                    transform.translation.y += TILE_SIZE.y * TILE_SCALE.y
-                } else if keys.just_pressed(KeyCode::Down)
-                    && player_move_ok(*grid_pos, Direction::Down, &tiles_query, &selected_board)
-                {
-                    grid_pos.y_pos -= 1;
-                    transform.translation.y -= TILE_SIZE.y * TILE_SCALE.y
+                let wanted_dir = get_wanted_direction(&keys, board.topology);
+
+                let tiles = collect_passages(&tiles_query);
+                let destination = match wanted_dir {
+                    Some(dir) => get_step(*grid_pos, *level, &dir, &tiles, board)
+                        .or_else(|| step_through_wall(&mut power_cards, *grid_pos, &dir, board)),
+                    None if keys.just_pressed(KeyCode::E) => {
+                        get_teleport_destination(*grid_pos, &tiles, board)
+                            .map(|pos| (pos, BridgeLevel::Over))
+                    }
+                    None => None,
+                };
+
+                if let Some((new_pos, new_level)) = destination {
+                    // if this movement is legal, move the player
+                    transform.translation += (board.topology.to_world(&new_pos)
+                        - board.topology.to_world(&grid_pos))
+                    .extend(0.0);
+                    *grid_pos = new_pos;
+                    *level = new_level;
+                    sounds.send(SoundEffect::Footstep);
                 }
             }
         }
     }
 }
 
-fn player_move_ok(
-    prev_pos: GridPosition,
-    wanted_dir: Direction,
-    tiles_query: &Query<(&OpenWays, &GridPosition), Without<Player>>,
-    selected_board: &Res<SelectedBoard>,
-) -> bool {
-    // Check if a desired move is legal (no walls, no outside board)
+fn get_wanted_direction(keys: &Input<KeyCode>, topology: Topology) -> Option<Direction> {
+    // On hex boards, the up and down arrows go along the diagonals of the board
+    // and Shift mirrors them
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let (up, down) = match (topology, shift) {
+        (Topology::Square, _) => (Direction::Up, Direction::Down),
+        (Topology::Hex, false) => (Direction::UpRight, Direction::DownLeft),
+        (Topology::Hex, true) => (Direction::UpLeft, Direction::DownRight),
+    };
+    if keys.just_pressed(KeyCode::Right) {
+        Some(Direction::Right)
+    } else if keys.just_pressed(KeyCode::Left) {
+        Some(Direction::Left)
+    } else if keys.just_pressed(KeyCode::Up) {
+        Some(up)
+    } else if keys.just_pressed(KeyCode::Down) {
+        Some(down)
+    } else {
+        None
+    }
+}
 
-    let (max_x, max_y) = get_max_coords(selected_board);
+pub fn collect_passages(tiles_query: &TilePassagesQuery) -> HashMap<GridPosition, TilePassages> {
+    tiles_query
+        .iter()
+        .map(|(grid_pos, tile_type, open_ways, entry_ways, under_ways)| {
+            (
+                *grid_pos,
+                TilePassages {
+                    tile_type: *tile_type,
+                    open_ways: *open_ways,
+                    entry_ways: *entry_ways,
+                    under_ways: *under_ways,
+                },
+            )
+        })
+        .collect()
+}
+
+pub fn get_step(
+    from: GridPosition,
+    level: BridgeLevel,
+    dir: &Direction,
+    tiles: &HashMap<GridPosition, TilePassages>,
+    board: &Board,
+) -> Option<(GridPosition, BridgeLevel)> {
+    // Where a step in the desired direction leads, if it is legal
+    // (no walls, no outside board, no one-way passage backwards, no switching bridge passages)
 
     // The desired position after moving
-    let mut destination = GridPosition {
-        ..Default::default()
+    let destination = get_neighbour_pos(&from, dir);
+
+    if pos_is_external(&destination, board) {
+        // no going outside the board
+        return None;
+    }
+
+    let (Some(current), Some(dest)) = (tiles.get(&from), tiles.get(&destination)) else {
+        return None;
     };
-    match wanted_dir {
-        Direction::Up => {
-            destination.y_pos = prev_pos.y_pos + 1;
-            destination.x_pos = prev_pos.x_pos;
-        }
-        Direction::Down => {
-            destination.y_pos = prev_pos.y_pos - 1;
-            destination.x_pos = prev_pos.x_pos;
-        }
-        Direction::Right => {
-            destination.x_pos = prev_pos.x_pos + 1;
-            destination.y_pos = prev_pos.y_pos;
-        }
-        Direction::Left => {
-            destination.x_pos = prev_pos.x_pos - 1;
-            destination.y_pos = prev_pos.y_pos;
-        }
+    // Exits of the current tile, from the passage the player is in
+    let exits = match level {
+        BridgeLevel::Over => current.open_ways,
+        BridgeLevel::Under => current.under_ways.0,
+    };
+
+    if passage_is_open(&exits, &dest.entry_ways, dir) {
+        Some((destination, BridgeLevel::Over))
+    } else if passage_is_open(&exits, &EntryWays(dest.under_ways.0), dir) {
+        // going under a bridge
+        Some((destination, BridgeLevel::Under))
+    } else {
+        None
     }
+}
 
-    if pos_is_external(&destination, max_x, max_y) {
-        // no going outside the board
-        return false;
+pub fn get_teleport_destination(
+    from: GridPosition,
+    tiles: &HashMap<GridPosition, TilePassages>,
+    board: &Board,
+) -> Option<GridPosition> {
+    // A teleporter leads to the other teleporter of its kind, if it is on the board
+    let current = tiles.get(&from)?;
+    if current.tile_type.behaviour() != TileBehaviour::Teleporter {
+        return None;
+    }
+    let mut partners = tiles.iter().filter(|(pos, tile)| {
+        **pos != from && tile.tile_type == current.tile_type && !pos_is_external(pos, board)
+    });
+    match (partners.next(), partners.next()) {
+        (Some((pos, _)), None) => Some(*pos),
+        _ => None,
+    }
+}
+
+pub fn get_neighbour_pos(pos: &GridPosition, dir: &Direction) -> GridPosition {
+    let (step_x, step_y) = dir.offset();
+    GridPosition {
+        x_pos: pos.x_pos + step_x,
+        y_pos: pos.y_pos + step_y,
+    }
+}
+
+fn passage_is_open(from_ways: &OpenWays, to_entries: &EntryWays, dir: &Direction) -> bool {
+    // evaluates to true if the first tile can be left and the second entered for the desired move
+    from_ways.is_open(*dir) && to_entries.0.is_open(dir.opposite())
+}
+
+pub fn get_next_steps(
+    pos: GridPosition,
+    level: BridgeLevel,
+    tiles: &HashMap<GridPosition, TilePassages>,
+    board: &Board,
+) -> Vec<(GridPosition, BridgeLevel)> {
+    // Everywhere a single step or a teleport leads from pos
+    let mut next_steps = board
+        .topology
+        .directions()
+        .iter()
+        .filter_map(|dir| get_step(pos, level, dir, tiles, board))
+        .collect::<Vec<_>>();
+    if let Some(partner) = get_teleport_destination(pos, tiles, board) {
+        next_steps.push((partner, BridgeLevel::Over));
     }
+    next_steps
+}
 
-    // Openings of the current tile
-    let mut current_ways = OpenWays { ..default() };
-    // Openings of the destination tile
-    let mut dest_ways = OpenWays { ..default() };
+pub fn get_reachable_positions(
+    start: GridPosition,
+    start_level: BridgeLevel,
+    tiles: &HashMap<GridPosition, TilePassages>,
+    board: &Board,
+) -> Vec<GridPosition> {
+    // All of the positions a player can walk or teleport to from start, start included
+    let mut visited = vec![(start, start_level)];
+    let mut to_visit = vec![(start, start_level)];
 
-    for (open_ways, grid_pos) in tiles_query {
-        // get the openings
-        if grid_pos == &prev_pos {
-            current_ways = *open_ways;
+    while let Some((pos, level)) = to_visit.pop() {
+        for step in get_next_steps(pos, level, tiles, board) {
+            if !visited.contains(&step) {
+                visited.push(step);
+                to_visit.push(step);
+            }
         }
-        if grid_pos == &destination {
-            dest_ways = *open_ways;
+    }
+
+    let mut reachable = vec![];
+    for (pos, _) in visited {
+        if !reachable.contains(&pos) {
+            reachable.push(pos);
         }
     }
-    match wanted_dir {
-        // evaluates to true if both tiles are open for the desired move
-        Direction::Up => current_ways.top && dest_ways.bottom,
-        Direction::Down => current_ways.bottom && dest_ways.top,
-        Direction::Left => current_ways.left && dest_ways.right,
-        Direction::Right => current_ways.right && dest_ways.left,
+    reachable
+}
+
+fn highlight_reachable_tiles(
+    mut sprites_query: Query<(&GridPosition, &mut Sprite), With<TileType>>,
+    tiles_query: TilePassagesQuery,
+    player_query: Query<(&Player, &GridPosition, &BridgeLevel), Without<TileType>>,
+    game_state: Res<GamePhase>,
+    selected_board: Res<SelectedBoard>,
+    theme: Res<ChosenTheme>,
+) {
+    // Show where the current player can go during their move
+
+    let mut reachable = vec![];
+    if !game_state.tile_push_phase && !game_state.has_ended {
+        let tiles = collect_passages(&tiles_query);
+        for (player, grid_pos, level) in &player_query {
+            if player.id == game_state.current_player_id {
+                reachable =
+                    get_reachable_positions(*grid_pos, *level, &tiles, &selected_board.board);
+            }
+        }
+    }
+
+    for (grid_pos, mut sprite) in &mut sprites_query {
+        let color = if reachable.contains(grid_pos) {
+            theme.get().colors.reachable
+        } else {
+            Color::WHITE
+        };
+        if sprite.color != color {
+            sprite.color = color;
+        }
     }
 }
 
 fn move_current_tile(
-    mut tiles_query: Query<(&mut GridPosition, &mut Transform, &mut OpenWays), With<TileType>>,
+    mut stacks_query: SpareStacksQuery,
+    mut push_slots: ResMut<PushSlots>,
     game_state: Res<GamePhase>,
     selected_board: Res<SelectedBoard>,
     keys: Res<Input<KeyCode>>,
+    mut sounds: EventWriter<SoundEffect>,
 ) {
-    let (max_x, max_y) = get_max_coords(&selected_board);
-    if game_state.tile_push_phase && !game_state.has_ended {
-        for (mut grid_pos, mut transform, mut open_ways) in &mut tiles_query {
-            if pos_is_external(&grid_pos, max_x, max_y) {
-                // This is the external tile
-                if keys.just_pressed(KeyCode::Up)
-                    && tile_move_ok(&grid_pos, Direction::Up, max_x, max_y)
-                {
-                    if grid_pos.y_pos == -1 {
-                        // Move to the other side of the board
-                        grid_pos.y_pos = max_y + 1;
-                        transform.translation.y = (max_y + 1) as f32 * TILE_SIZE.y * TILE_SCALE.y;
-                    } else if grid_pos.y_pos < max_y {
-                        // move up along the board
-                        grid_pos.y_pos += 1;
-                        transform.translation.y += TILE_SIZE.y * TILE_SCALE.y;
-                    } else if grid_pos.y_pos == max_y {
-                        // move to the top side of the board
-                        grid_pos.y_pos += 1;
-                        if grid_pos.x_pos == -1 {
-                            // from the left side of the board
-                            grid_pos.x_pos += 1;
-                            transform.translation += Vec3::new(
-                                TILE_SIZE.x * TILE_SCALE.x,
-                                TILE_SIZE.y * TILE_SCALE.y,
-                                0.0,
-                            );
-                        }
-                        if grid_pos.x_pos == max_x + 1 {
-                            // from the right side of the board
-                            grid_pos.x_pos -= 1;
-
-                            transform.translation += Vec3::new(
-                                -TILE_SIZE.x * TILE_SCALE.x,
-                                TILE_SIZE.y * TILE_SCALE.y,
-                                0.0,
-                            );
-                        }
-                    }
-                } else if keys.just_pressed(KeyCode::Down)
-                    && tile_move_ok(&grid_pos, Direction::Down, max_x, max_y)
-                {
-                    if grid_pos.y_pos == max_y + 1 {
-                        // Move to the other side of the board
-                        grid_pos.y_pos = -1;
-                        transform.translation.y = -TILE_SIZE.y * TILE_SCALE.y;
-                    } else if grid_pos.y_pos > 0 {
-                        // move down along the board
-                        grid_pos.y_pos -= 1;
-                        transform.translation.y -= TILE_SIZE.y * TILE_SCALE.y;
-                    } else if grid_pos.y_pos == 0 {
-                        // move to the bottom side of the board
-                        grid_pos.y_pos -= 1;
-                        if grid_pos.x_pos == -1 {
-                            // from the left side of the board
-                            grid_pos.x_pos += 1;
-                            transform.translation += Vec3::new(
-                                TILE_SIZE.x * TILE_SCALE.x,
-                                -TILE_SIZE.y * TILE_SCALE.y,
-                                0.0,
-                            );
-                        }
-                        if grid_pos.x_pos == max_x + 1 {
-                            // from the right side of the board
-                            grid_pos.x_pos -= 1;
-                            transform.translation += Vec3::new(
-                                -TILE_SIZE.x * TILE_SCALE.x,
-                                -TILE_SIZE.y * TILE_SCALE.y,
-                                0.0,
-                            );
-                        }
-                    }
-                } else if keys.just_pressed(KeyCode::Right)
-                    && tile_move_ok(&grid_pos, Direction::Right, max_x, max_y)
-                {
-                    if grid_pos.x_pos == -1 {
-                        // Move to the other side of the board
-                        grid_pos.x_pos = max_x + 1;
-                        transform.translation.x = (max_x + 1) as f32 * TILE_SIZE.x * TILE_SCALE.x;
-                    } else if grid_pos.x_pos < max_x {
-                        // move right along the board
-                        grid_pos.x_pos += 1;
-                        transform.translation.x += TILE_SIZE.x * TILE_SCALE.x;
-                    } else if grid_pos.x_pos == max_x {
-                        // move to the right side of the board
-                        grid_pos.x_pos += 1;
-                        if grid_pos.y_pos == -1 {
-                            // from the bottom side of the board
-                            grid_pos.y_pos += 1;
-                            transform.translation += Vec3::new(
-                                TILE_SIZE.x * TILE_SCALE.x,
-                                TILE_SIZE.y * TILE_SCALE.y,
-                                0.0,
-                            );
-                        }
-                        if grid_pos.y_pos == max_y + 1 {
-                            // from the top side of the board
-                            grid_pos.y_pos -= 1;
-                            transform.translation += Vec3::new(
-                                TILE_SIZE.x * TILE_SCALE.x,
-                                -TILE_SIZE.y * TILE_SCALE.y,
-                                0.0,
-                            );
-                        }
-                    }
-                } else if keys.just_pressed(KeyCode::Left)
-                    && tile_move_ok(&grid_pos, Direction::Left, max_x, max_y)
-                {
-                    if grid_pos.x_pos == max_x + 1 {
-                        // Move to the other side of the board
-                        grid_pos.x_pos = -1;
-                        transform.translation.x = -TILE_SIZE.x * TILE_SCALE.x;
-                    } else if grid_pos.x_pos > 0 {
-                        // move left along the board
-                        grid_pos.x_pos -= 1;
-                        transform.translation.x -= TILE_SIZE.x * TILE_SCALE.x;
-                    } else if grid_pos.x_pos == 0 {
-                        // move to the left side of the board
-                        grid_pos.x_pos -= 1;
-                        if grid_pos.y_pos == -1 {
-                            // from the bottom side of the board
-                            grid_pos.y_pos += 1;
-                            transform.translation += Vec3::new(
-                                -TILE_SIZE.x * TILE_SCALE.x,
-                                TILE_SIZE.y * TILE_SCALE.y,
-                                0.0,
-                            );
-                        }
-                        if grid_pos.y_pos == max_y + 1 {
-                            // from the top side of the board
-                            grid_pos.y_pos -= 1;
-                            transform.translation += Vec3::new(
-                                -TILE_SIZE.x * TILE_SCALE.x,
-                                -TILE_SIZE.y * TILE_SCALE.y,
-                                0.0,
-                            );
-                        }
-                    }
-                }
-                if keys.just_pressed(KeyCode::R) {
-                    *open_ways = rotate_ways(*open_ways, -PI / 2.0);
-                    transform.rotate_axis(Vec3::new(0.0, 0.0, 1.0), -PI / 2.0);
-                }
+    if !game_state.tile_push_phase || game_state.has_ended {
+        return;
+    }
+    // The spare tile to push is the one in the current slot
+    let Some(spare_pos) = push_slots
+        .slots
+        .get(push_slots.current)
+        .map(|slot| slot.pos)
+    else {
+        return;
+    };
+    let topology = push_slots.topology;
+
+    if keys.just_pressed(KeyCode::R) {
+        for (grid_pos, _, orientation) in &mut stacks_query {
+            if let (true, Some(mut orientation)) = (*grid_pos == spare_pos, orientation) {
+                // Shift+R turns the other way
+                *orientation = if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+                    orientation.rotated_anticlockwise()
+                } else {
+                    orientation.rotated_clockwise()
+                };
+                sounds.send(SoundEffect::TileRotate);
             }
         }
     }
+
+    let wanted_dir = if keys.just_pressed(KeyCode::Up) {
+        Some(Vec2::Y)
+    } else if keys.just_pressed(KeyCode::Down) {
+        Some(Vec2::NEG_Y)
+    } else if keys.just_pressed(KeyCode::Right) {
+        Some(Vec2::X)
+    } else if keys.just_pressed(KeyCode::Left) {
+        Some(Vec2::NEG_X)
+    } else {
+        None
+    };
+    if let Some(index) = wanted_dir.and_then(|dir| push_slots.next_slot(dir)) {
+        // Move along the push slots around the board
+        push_slots.current = index;
+        move_stacks(
+            &mut stacks_query,
+            &[(spare_pos, push_slots.slots[index].pos)],
+            topology,
+        );
+    } else if keys.just_pressed(KeyCode::C) {
+        // Take the first spare of the pool, the current one goes to the back of the pool
+        let pool = selected_board.board.spare_pool();
+        if let (Some(&first), Some(&last)) = (pool.first(), pool.last()) {
+            let mut moves = vec![(first, spare_pos), (spare_pos, last)];
+            moves.extend(pool.windows(2).map(|pair| (pair[1], pair[0])));
+            move_stacks(&mut stacks_query, &moves, topology);
+            sounds.send(SoundEffect::TileSlide);
+        }
+    }
 }
 
-fn tile_move_ok(grid_pos: &GridPosition, wanted_dir: Direction, max_x: i32, max_y: i32) -> bool {
-    match wanted_dir {
-        Direction::Up => grid_pos.y_pos < max_y + 1,
-        Direction::Down => grid_pos.y_pos > -1,
-        Direction::Left => grid_pos.x_pos > -1,
-        Direction::Right => grid_pos.x_pos < max_x + 1,
+pub fn move_stacks(
+    stacks_query: &mut SpareStacksQuery,
+    moves: &[(GridPosition, GridPosition)],
+    topology: Topology,
+) {
+    // Move the tiles and treasures of each origin to its destination, all at once
+    for (mut grid_pos, mut transform, _) in stacks_query.iter_mut() {
+        if let Some(&(_, to)) = moves.iter().find(|(from, _)| *from == *grid_pos) {
+            *grid_pos = to;
+            let world = topology.to_world(&to);
+            transform.translation.x = world.x;
+            transform.translation.y = world.y;
+        }
     }
 }
 
-fn trigger_push(
-    mut entities_query: Query<(&mut Transform, &mut GridPosition)>,
-    selected_board: Res<SelectedBoard>,
+pub fn trigger_push(
+    mut entities_query: PushedEntitiesQuery,
+    mut push_slots: ResMut<PushSlots>,
     keys: Res<Input<KeyCode>>,
     mut game_state: ResMut<GamePhase>,
     mut illegal: ResMut<IllegalPushPositions>,
+    mut sounds: EventWriter<SoundEffect>,
 ) {
-    let (max_x, max_y) = get_max_coords(&selected_board);
-
-    let mut external_pos = GridPosition {
-        ..Default::default()
-    };
-
-    for (_, grid_pos) in &mut entities_query {
-        if pos_is_external(&grid_pos, max_x, max_y) {
-            external_pos = *grid_pos;
-        }
+    if !game_state.tile_push_phase || game_state.has_ended {
+        return;
     }
+    let current = push_slots.current;
 
-    if game_state.tile_push_phase
-        && !illegal.positions.contains(&external_pos)
-        && !game_state.has_ended
-    {
+    if illegal.is_illegal(current) {
+        if keys.just_pressed(KeyCode::Return) || keys.just_pressed(KeyCode::S) {
+            // Pushing from here is forbidden
+            sounds.send(SoundEffect::IllegalPush);
+        }
+    } else {
         if keys.just_pressed(KeyCode::Return) && !keys.pressed(KeyCode::S) {
-            push_tile(&mut entities_query, external_pos, max_x, max_y);
+            push_from_current_slot(&mut push_slots, &mut entities_query, &mut illegal);
+            sounds.send(SoundEffect::TileSlide);
             game_state.tile_push_phase = false;
-            if illegal.fresh_pos_on_top {
-                illegal.positions.pop();
-            }
-            illegal
-                .positions
-                .push(get_opposite_pos(&external_pos, max_x, max_y));
-            illegal.fresh_pos_on_top = true;
-            illegal.positions.retain(|x| *x != external_pos);
         }
         if keys.just_pressed(KeyCode::S) {
             // Simulated push
-            push_tile(&mut entities_query, external_pos, max_x, max_y);
+            push_slots.push(&mut entities_query);
+            sounds.send(SoundEffect::TileSlide);
         }
         if keys.just_released(KeyCode::S) {
             // End of simulation
-            push_tile(&mut entities_query, external_pos, max_x, max_y);
+            push_slots.push(&mut entities_query);
         }
     }
 }
 
-fn push_tile(
-    entities_query: &mut Query<(&mut Transform, &mut GridPosition)>,
-    external_pos: GridPosition,
-    max_x: i32,
-    max_y: i32,
+// The push of a turn, after which the opposite slot can't be used until the next push
+pub fn push_from_current_slot(
+    push_slots: &mut PushSlots,
+    entities_query: &mut PushedEntitiesQuery,
+    illegal: &mut IllegalPushPositions,
 ) {
-    for (mut transform, mut grid_pos) in entities_query.iter_mut() {
-        if external_pos.x_pos == -1 {
-            // pushing from the left side of the board
-            if grid_pos.y_pos == external_pos.y_pos {
-                // tiles horizontally aligned with the external
-                grid_pos.x_pos += 1;
-                transform.translation.x += TILE_SIZE.x * TILE_SCALE.x;
-            }
-        } else if external_pos.y_pos == -1 {
-            // pushing from the bottom side of the board
-            if grid_pos.x_pos == external_pos.x_pos {
-                // tiles vertically aligned with the external
-                grid_pos.y_pos += 1;
-                transform.translation.y += TILE_SIZE.y * TILE_SCALE.y;
-            }
-        } else if external_pos.x_pos == max_x + 1 {
-            // pushing from the right side of the board
-            if grid_pos.y_pos == external_pos.y_pos {
-                // tiles horizontally aligned with the external
-                grid_pos.x_pos -= 1;
-                transform.translation.x -= TILE_SIZE.x * TILE_SCALE.x;
-            }
-        } else if external_pos.y_pos == max_y + 1 {
-            // pushing from the top side of the board
-            if grid_pos.x_pos == external_pos.x_pos {
-                // tiles vertically aligned with the external
-                grid_pos.y_pos -= 1;
-                transform.translation.y -= TILE_SIZE.y * TILE_SCALE.y;
-            }
-        }
+    let current = push_slots.current;
+    let opposite = push_slots.push(entities_query);
+    if illegal.fresh_pos_on_top {
+        illegal.positions.pop();
     }
+    illegal.positions.extend(opposite);
+    illegal.fresh_pos_on_top = opposite.is_some();
+    illegal.positions.retain(|x| *x != current);
 }
 
-fn get_opposite_pos(pos: &GridPosition, max_x: i32, max_y: i32) -> GridPosition {
-    if pos.x_pos == -1 {
-        // position is on the left side
-        GridPosition {
-            x_pos: max_x + 1,
-            y_pos: pos.y_pos,
-        }
-    } else if pos.y_pos == -1 {
-        // position is on the bottom side
-        GridPosition {
-            x_pos: pos.x_pos,
-            y_pos: max_y + 1,
-        }
-    } else if pos.x_pos == max_x + 1 {
-        // position is on the right side
-        GridPosition {
-            x_pos: -1,
-            y_pos: pos.y_pos,
-        }
-    } else if pos.y_pos == max_y + 1 {
-        // position is on the top side
-        GridPosition {
-            x_pos: pos.x_pos,
-            y_pos: -1,
-        }
-    } else {
-        *pos
-    }
-}
-
-fn compute_illegal_pushes(
+fn setup_push_slots(
+    mut commands: Commands,
+    mut push_slots: ResMut<PushSlots>,
     mut illegal: ResMut<IllegalPushPositions>,
     selected_board: Res<SelectedBoard>,
+    asset_server: Res<AssetServer>,
+    theme: Res<ChosenTheme>,
 ) {
-    let (max_x, max_y) = get_max_coords(&selected_board);
-
-    for y_pos in 0..=max_y {
-        for x_pos in 0..=max_x {
-            let current_tile = selected_board.board.tiles[[y_pos as usize, x_pos as usize]];
+    // The spare tile starts in the first slot
+    *push_slots = PushSlots {
+        slots: selected_board.board.push_slots(),
+        current: 0,
+        topology: selected_board.board.topology,
+    };
+    illegal.fresh_pos_on_top = false;
+    illegal.positions.clear();
 
-            if matches!(current_tile.can_move, CanMove::No) {
-                let new_illegal_left = GridPosition {
-                    x_pos: -1,
-                    y_pos: max_y - y_pos,
-                };
-                let new_illegal_bottom = GridPosition { x_pos, y_pos: -1 };
-                let new_illegal_right = GridPosition {
-                    x_pos: max_x + 1,
-                    y_pos: max_y - y_pos,
-                };
-                let new_illegal_top = GridPosition {
-                    x_pos,
-                    y_pos: max_y + 1,
-                };
-                if !illegal.positions.contains(&new_illegal_left) {
-                    illegal.positions.push(new_illegal_left);
-                }
-                if !illegal.positions.contains(&new_illegal_bottom) {
-                    illegal.positions.push(new_illegal_bottom);
-                }
-                if !illegal.positions.contains(&new_illegal_right) {
-                    illegal.positions.push(new_illegal_right);
-                }
-                if !illegal.positions.contains(&new_illegal_top) {
-                    illegal.positions.push(new_illegal_top);
-                }
-            }
-        }
+    for (index, slot) in push_slots.slots.iter().enumerate() {
+        let point = push_slots.slot_point(slot, 0.3);
+        let towards = point - push_slots.slot_point(slot, 0.0);
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("push_slot.png"),
+                sprite: Sprite {
+                    color: theme.get().colors.slot,
+                    ..default()
+                },
+                // On the side of the slot the tiles are pushed to, pointing to them
+                transform: Transform {
+                    translation: point.extend(0.5),
+                    rotation: Quat::from_rotation_z(towards.y.atan2(towards.x)),
+                    scale: TILE_SCALE,
+                },
+                ..default()
+            },
+            PushSlotMarker { index },
+        ));
     }
 }
 
-fn warp_player(
-    mut player_query: Query<(&mut GridPosition, &mut Transform), With<Player>>,
-    selected_board: Res<SelectedBoard>,
+fn highlight_push_slots(
+    mut marker_query: Query<(&PushSlotMarker, &mut Sprite)>,
+    push_slots: Res<PushSlots>,
+    illegal: Res<IllegalPushPositions>,
+    game_state: Res<GamePhase>,
+    theme: Res<ChosenTheme>,
 ) {
-    let (max_x, max_y) = get_max_coords(&selected_board);
-    for (mut grid_pos, mut transform) in &mut player_query {
-        if grid_pos.x_pos == -1 {
-            // Player is on the left side of the board
-            grid_pos.x_pos = max_x;
-            transform.translation.x = max_x as f32 * TILE_SIZE.x * TILE_SCALE.x;
-        } else if grid_pos.y_pos == -1 {
-            // Player is on the bottom side of the board
-            grid_pos.y_pos = max_y;
-            transform.translation.y = max_y as f32 * TILE_SIZE.y * TILE_SCALE.y;
-        } else if grid_pos.x_pos == max_x + 1 {
-            // Player is on the right side of the board
-            grid_pos.x_pos = 0;
-            transform.translation.x = 0.0;
-        } else if grid_pos.y_pos == max_y + 1 {
-            // Player is on the top side of the board
-            grid_pos.y_pos = 0;
-            transform.translation.y = 0.0;
+    // Show which slot the spare tile will be pushed from, and the slots that can't be used
+    let colors = theme.get().colors;
+    for (marker, mut sprite) in &mut marker_query {
+        let color = if illegal.is_illegal(marker.index) {
+            colors.illegal_slot
+        } else if marker.index == push_slots.current && game_state.tile_push_phase {
+            colors.current_slot
+        } else {
+            colors.slot
+        };
+        if sprite.color != color {
+            sprite.color = color;
         }
     }
 }
 
-pub fn get_max_coords(selected_board: &Res<SelectedBoard>) -> (i32, i32) {
-    let max_x: i32 = (selected_board.board.tiles.shape()[1] - 1)
-        .try_into()
-        .unwrap();
-    let max_y: i32 = (selected_board.board.tiles.shape()[0] - 1)
-        .try_into()
-        .unwrap();
-    (max_x, max_y)
+fn cleanup_push_slots(mut commands: Commands, marker_query: Query<Entity, With<PushSlotMarker>>) {
+    for entity in &marker_query {
+        commands.entity(entity).despawn();
+    }
 }
 
-pub fn pos_is_external(pos: &GridPosition, max_x: i32, max_y: i32) -> bool {
-    pos.x_pos == -1 || pos.y_pos == -1 || pos.x_pos > max_x || pos.y_pos > max_y
+pub fn pos_is_external(pos: &GridPosition, board: &Board) -> bool {
+    // Outside of the board, or in one of its holes
+    !board.contains(pos)
 }
@@ -1,13 +1,15 @@
 use bevy::prelude::*;
 
 use crate::{
-    movement::CanMove, player::Player, treasure::CollectedLists, GamePhase, NUM_PLAYERS,
-    TREASURES_TO_GET,
+    handover::turn_is_handed_over, movement::CanMove, player::Player, sound::SoundEffect,
+    teams::Teams, theme::themes_are_loaded, treasure::TreasureLists, GamePhase, GameSettings,
 };
 
 #[derive(Debug, States, PartialEq, Eq, Hash, Clone, Default)]
 pub enum GameState {
+    // until the themes the menu and the game are dressed with are loaded
     #[default]
+    Loading,
     MainMenu,
     Playing,
 }
@@ -17,14 +19,26 @@ pub struct GamePhasePlugin;
 impl Plugin for GamePhasePlugin {
     fn build(&self, app: &mut App) {
         app.add_state::<GameState>()
+            .add_systems(
+                Update,
+                finish_loading
+                    .run_if(in_state(GameState::Loading))
+                    .run_if(themes_are_loaded),
+            )
             .add_systems(Update, start_playing.run_if(in_state(GameState::MainMenu)))
             .add_systems(
                 Update,
-                (check_phase, stop_playing).run_if(in_state(GameState::Playing)),
-            );
+                (check_phase.run_if(turn_is_handed_over), stop_playing)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), reset_game_phase);
     }
 }
 
+fn finish_loading(mut game_phase: ResMut<NextState<GameState>>) {
+    game_phase.set(GameState::MainMenu);
+}
+
 fn start_playing(keys: Res<Input<KeyCode>>, mut game_phase: ResMut<NextState<GameState>>) {
     if keys.just_pressed(KeyCode::Return) {
         game_phase.set(GameState::Playing);
@@ -36,11 +50,23 @@ fn stop_playing(keys: Res<Input<KeyCode>>, mut game_phase: ResMut<NextState<Game
     }
 }
 
+fn reset_game_phase(mut game_phase: ResMut<GamePhase>) {
+    // The next game starts from the first turn
+    *game_phase = GamePhase {
+        current_player_id: 0,
+        tile_push_phase: true,
+        has_ended: false,
+        round: 0,
+    };
+}
+
 fn check_phase(
     mut player_query: Query<(&Player, &mut CanMove)>,
     mut game_phase: ResMut<GamePhase>,
-    collected_treasures: Res<CollectedLists>,
+    treasure_lists: Res<TreasureLists>,
+    game_settings: Res<GameSettings>,
     keys: Res<Input<KeyCode>>,
+    mut sounds: EventWriter<SoundEffect>,
 ) {
     for (player, mut can_move) in &mut player_query {
         // if it is a player's turn, allow them to move
@@ -51,25 +77,56 @@ fn check_phase(
         }
     }
     if keys.just_pressed(KeyCode::T) {
-        end_turn(&mut game_phase, &collected_treasures)
+        end_turn(
+            &mut game_phase,
+            &treasure_lists,
+            &game_settings,
+            &mut sounds,
+        )
     }
 }
 
-pub fn end_turn(game_state: &mut GamePhase, collected_treasures: &CollectedLists) {
+pub fn end_turn(
+    game_state: &mut GamePhase,
+    treasure_lists: &TreasureLists,
+    game_settings: &GameSettings,
+    sounds: &mut EventWriter<SoundEffect>,
+) {
+    let had_ended = game_state.has_ended;
+    let previous_player_id = game_state.current_player_id;
+    let num_players = game_settings.num_players;
+    // a player has finished once the deck of their team is empty
+    let has_finished = |player_id: i32| {
+        treasure_lists
+            .lists
+            .get(&game_settings.teams.team_of(player_id))
+            .is_none_or(|list| list.is_empty())
+    };
+    if game_settings.teams != Teams::Solo && (0..num_players).any(has_finished) {
+        // the first team to empty its deck wins
+        game_state.has_ended = true;
+    }
+
     // end turn
     game_state.tile_push_phase = true;
-    game_state.current_player_id = (game_state.current_player_id + 1) % NUM_PLAYERS;
+    game_state.current_player_id = (game_state.current_player_id + 1) % num_players;
     let mut num_players_finished = 0;
-    while (TREASURES_TO_GET as usize
-        == collected_treasures
-            .lists
-            .get(&game_state.current_player_id)
-            .unwrap()
-            .len())
-        && !game_state.has_ended
-    {
-        game_state.current_player_id = (game_state.current_player_id + 1) % NUM_PLAYERS;
+    while has_finished(game_state.current_player_id) && !game_state.has_ended {
+        game_state.current_player_id = (game_state.current_player_id + 1) % num_players;
         num_players_finished += 1;
-        game_state.has_ended = num_players_finished == NUM_PLAYERS;
+        game_state.has_ended = num_players_finished == num_players;
+    }
+
+    if game_state.current_player_id <= previous_player_id {
+        // the turn went around the table
+        game_state.round += 1;
+    }
+
+    if game_state.has_ended {
+        if !had_ended {
+            sounds.send(SoundEffect::GameEnd);
+        }
+    } else {
+        sounds.send(SoundEffect::TurnChange);
     }
 }
@@ -2,24 +2,20 @@ use bevy::prelude::*;
 
 use crate::{
     actors::{get_random_pos_on_axis, GridAxis, SpawnPosition},
+    avatars::PlayerProfile,
     board_selector::SelectedBoard,
-    movement::CanMove,
+    movement::{BridgeLevel, CanMove},
     phases::GameState,
-    tile::{TILE_SCALE, TILE_SIZE},
-    GamePhase, GameSettings, GridPosition,
+    rng::GameRng,
+    topology::Topology,
+    GameSettings, GridPosition,
 };
 
-const TOKEN_SCALE: Vec3 = Vec3::new(0.4, 0.4, 0.0);
+// Not flattened on z, so that the ring around a token stays above it
+const TOKEN_SCALE: Vec3 = Vec3::new(0.4, 0.4, 1.0);
 // const TOKEN_SIZE: Vec3 = Vec3::new(280.0, 280.0, 0.0);
 const WIGGLE_VALUE: f32 = 20.0;
 
-const SPRITES: [&str; 4] = [
-    "players/Commoner.png",
-    "players/Genie.png",
-    "players/Harengon.png",
-    "players/Speaker.png",
-];
-
 #[derive(Resource, Debug, Default)]
 struct WiggledPlayers {
     pairs: Vec<PlayerPair>,
@@ -34,6 +30,8 @@ struct PlayerPair {
 #[derive(Component, Debug)]
 pub struct Player {
     pub id: i32,
+    // Where the player started, and goes back to when caught by the minotaur
+    pub spawn: GridPosition,
 }
 
 #[derive(Bundle)]
@@ -42,6 +40,7 @@ struct PlayerBundle {
     pos: GridPosition,
     sprite: SpriteBundle,
     can_move: CanMove,
+    level: BridgeLevel,
 }
 
 pub struct PlayerPlugin;
@@ -52,10 +51,7 @@ impl Plugin for PlayerPlugin {
             ..Default::default()
         })
         .add_systems(OnEnter(GameState::Playing), spawn_all_players)
-        .add_systems(
-            Update,
-            (unstack_players, display_current_player).run_if(in_state(GameState::Playing)),
-        )
+        .add_systems(Update, unstack_players.run_if(in_state(GameState::Playing)))
         .add_systems(OnExit(GameState::Playing), cleanup_players);
     }
 }
@@ -65,6 +61,8 @@ fn spawn_all_players(
     game_settings: Res<GameSettings>,
     selected_board: Res<SelectedBoard>,
     asset_server: Res<AssetServer>,
+    mut game_rng: ResMut<GameRng>,
+    profile: Res<PlayerProfile>,
 ) {
     // The number of specified spawn positions in the selected board's data
     let num_spawn_pos: i32 = selected_board
@@ -91,21 +89,28 @@ fn spawn_all_players(
                     y_pos,
                     &mut commands,
                     &asset_server,
-                    SPRITES[(id % 4) as usize],
+                    &profile.avatar(id).token_path(),
+                    selected_board.board.topology,
                 );
                 used_pos.push(GridPosition { x_pos, y_pos });
             }
             // If the specified position is not set, pick a random position
             // without superposition if possible
             SpawnPosition::Any => {
-                let mut x_pos = get_random_pos_on_axis(GridAxis::X, &selected_board);
-                let mut y_pos = get_random_pos_on_axis(GridAxis::Y, &selected_board);
+                let mut x_pos =
+                    get_random_pos_on_axis(GridAxis::X, &selected_board, &mut game_rng.spawns);
+                let mut y_pos =
+                    get_random_pos_on_axis(GridAxis::Y, &selected_board, &mut game_rng.spawns);
                 // while not new position AND there are positions without players
-                while used_pos.contains(&GridPosition { x_pos, y_pos })
-                    && used_pos.len() < (game_settings.num_players - 1).try_into().unwrap()
+                // and never in a hole of the board
+                while (used_pos.contains(&GridPosition { x_pos, y_pos })
+                    && used_pos.len() < (game_settings.num_players - 1).try_into().unwrap())
+                    || selected_board.board.is_void(&GridPosition { x_pos, y_pos })
                 {
-                    x_pos = get_random_pos_on_axis(GridAxis::X, &selected_board);
-                    y_pos = get_random_pos_on_axis(GridAxis::Y, &selected_board);
+                    x_pos =
+                        get_random_pos_on_axis(GridAxis::X, &selected_board, &mut game_rng.spawns);
+                    y_pos =
+                        get_random_pos_on_axis(GridAxis::Y, &selected_board, &mut game_rng.spawns);
                 }
                 spawn_player(
                     id,
@@ -113,7 +118,8 @@ fn spawn_all_players(
                     y_pos,
                     &mut commands,
                     &asset_server,
-                    SPRITES[(id % 4) as usize],
+                    &profile.avatar(id).token_path(),
+                    selected_board.board.topology,
                 );
                 used_pos.push(GridPosition { x_pos, y_pos });
             }
@@ -128,24 +134,27 @@ fn spawn_player(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     sprite_path: &str,
+    topology: Topology,
 ) {
     commands.spawn(PlayerBundle {
-        player: Player { id },
+        player: Player {
+            id,
+            spawn: GridPosition { x_pos, y_pos },
+        },
         pos: GridPosition { x_pos, y_pos },
         sprite: SpriteBundle {
             texture: asset_server.load(sprite_path.to_owned()),
             transform: Transform {
-                translation: Vec3::new(
-                    x_pos as f32 * TILE_SIZE.x * TILE_SCALE.x,
-                    y_pos as f32 * TILE_SIZE.y * TILE_SCALE.y,
-                    1.0,
-                ),
+                translation: topology
+                    .to_world(&GridPosition { x_pos, y_pos })
+                    .extend(1.0),
                 scale: TOKEN_SCALE,
                 ..default()
             },
             ..default()
         },
         can_move: CanMove::No,
+        level: BridgeLevel::Over,
     });
 }
 
@@ -181,31 +190,6 @@ fn unstack_players(
     }
 }
 
-fn display_current_player(
-    mut commands: Commands,
-    player_query: Query<(&Player, Entity)>,
-    game_state: Res<GamePhase>,
-    asset_server: Res<AssetServer>,
-    keys: Res<Input<KeyCode>>,
-) {
-    if keys.just_pressed(KeyCode::Space) {
-        spawn_player(
-            -1,
-            -2,
-            3,
-            &mut commands,
-            &asset_server,
-            SPRITES[(game_state.current_player_id % 4) as usize],
-        );
-    } else if keys.just_released(KeyCode::Space) {
-        for (player, entity) in &player_query {
-            if player.id == -1 {
-                commands.entity(entity).despawn_recursive();
-            }
-        }
-    }
-}
-
 fn cleanup_players(mut commands: Commands, player_query: Query<Entity, With<Player>>) {
     for entity in &player_query {
         commands.entity(entity).despawn_recursive();
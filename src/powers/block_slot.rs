@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+
+use super::{PowerCard, PowerCards};
+use crate::{
+    movement::{IllegalPushPositions, PushSlots},
+    sound::SoundEffect,
+    theme::ChosenTheme,
+    tile::{TILE_SCALE, TILE_SIZE},
+    GamePhase,
+};
+
+// A push slot nobody can push from, until the next turn of the player who blocked it
+#[derive(Debug, Clone, Copy)]
+pub struct BlockedSlot {
+    // index of the slot in PushSlots
+    pub slot: usize,
+    owner: i32,
+    round: u32,
+}
+
+// Shows a blocked slot
+// Not a GridPosition, so that pushes don't move it
+#[derive(Component, Debug)]
+pub struct BlockedSlotMarker(usize);
+
+// Played while pushing: blocks the slot the spare tile is in
+pub fn block_spare_slot(
+    mut commands: Commands,
+    mut power_cards: ResMut<PowerCards>,
+    mut illegal: ResMut<IllegalPushPositions>,
+    game_state: Res<GamePhase>,
+    push_slots: Res<PushSlots>,
+    theme: Res<ChosenTheme>,
+    mut sounds: EventWriter<SoundEffect>,
+) {
+    if power_cards.armed() != Some(PowerCard::BlockSlot) {
+        return;
+    }
+    let slot = push_slots.current;
+    let Some(pos) = push_slots.slots.get(slot).map(|push_slot| push_slot.pos) else {
+        return;
+    };
+
+    if illegal.is_illegal(slot) {
+        // Nothing to block, the card goes back to the hand
+        power_cards.armed = None;
+        sounds.send(SoundEffect::IllegalPush);
+        return;
+    }
+
+    illegal.blocked.push(BlockedSlot {
+        slot,
+        owner: game_state.current_player_id,
+        round: game_state.round,
+    });
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: theme.get().colors.blocked_slot,
+                custom_size: Some(Vec2::new(
+                    TILE_SIZE.x * TILE_SCALE.x,
+                    TILE_SIZE.y * TILE_SCALE.y,
+                )),
+                ..default()
+            },
+            // Above the spare tile
+            transform: Transform::from_translation(push_slots.topology.to_world(&pos).extend(0.5)),
+            ..default()
+        },
+        BlockedSlotMarker(slot),
+    ));
+    sounds.send(SoundEffect::IllegalPush);
+    power_cards.consume();
+}
+
+pub fn unblock_slots(
+    mut commands: Commands,
+    marker_query: Query<(Entity, &BlockedSlotMarker)>,
+    mut illegal: ResMut<IllegalPushPositions>,
+    game_state: Res<GamePhase>,
+) {
+    // A slot is freed when the turn of its owner comes back (or after, if they have finished)
+    let now = (game_state.round, game_state.current_player_id);
+    illegal
+        .blocked
+        .retain(|slot| now < (slot.round + 1, slot.owner));
+
+    for (entity, marker) in &marker_query {
+        if !illegal.blocked.iter().any(|slot| slot.slot == marker.0) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+pub fn clear_blocked_slots(
+    mut commands: Commands,
+    marker_query: Query<Entity, With<BlockedSlotMarker>>,
+    mut illegal: ResMut<IllegalPushPositions>,
+) {
+    illegal.blocked.clear();
+    for entity in &marker_query {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,361 @@
+mod block_slot;
+mod push_twice;
+mod rotate_fixed;
+mod swap_places;
+mod wall_pass;
+
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap, window::PrimaryWindow};
+use rand::seq::SliceRandom;
+
+use crate::{
+    board_selector::SelectedBoard,
+    bots::human_is_playing,
+    handover::{turn_is_handed_over, HandOver},
+    movement::trigger_push,
+    phases::GameState,
+    rng::GameRng,
+    sound::SoundEffect,
+    theme::{ChosenTheme, ThemeColor},
+    treasure::CollectedLists,
+    ui::{ChosenLanguage, Language},
+    GamePhase, GameSettings, GridPosition,
+};
+
+pub use block_slot::BlockedSlot;
+pub use wall_pass::step_through_wall;
+
+// Maximum number of cards held by a player
+const HAND_SIZE: usize = 3;
+// Everyone draws a card every few rounds
+const CARD_ROUND_INTERVAL: u32 = 3;
+const FONT_SIZE: f32 = 22.0;
+const CARD_KEYS: [KeyCode; HAND_SIZE] = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerCard {
+    // Push two tiles this turn
+    PushTwice,
+    // Rotate a tile that cannot be pushed
+    RotateFixed,
+    // Swap places with an opponent
+    SwapPlaces,
+    // Walk through one wall
+    WallPass,
+    // Forbid pushing from the slot of the spare tile until the next turn of the player
+    BlockSlot,
+}
+
+const ALL_CARDS: [PowerCard; 5] = [
+    PowerCard::PushTwice,
+    PowerCard::RotateFixed,
+    PowerCard::SwapPlaces,
+    PowerCard::WallPass,
+    PowerCard::BlockSlot,
+];
+
+impl PowerCard {
+    // Whether the card is played while pushing tiles (or while moving, if false)
+    fn in_push_phase(self) -> bool {
+        match self {
+            PowerCard::PushTwice | PowerCard::RotateFixed | PowerCard::BlockSlot => true,
+            PowerCard::SwapPlaces | PowerCard::WallPass => false,
+        }
+    }
+
+    fn name(self, language: &Language) -> &'static str {
+        match (language, self) {
+            (Language::French, PowerCard::PushTwice) => "Double poussee",
+            (Language::French, PowerCard::RotateFixed) => "Tourner une tuile fixe (clic)",
+            (Language::French, PowerCard::SwapPlaces) => "Echanger avec un adversaire (clic)",
+            (Language::French, PowerCard::WallPass) => "Traverser un mur",
+            (Language::French, PowerCard::BlockSlot) => "Bloquer l'emplacement de la tuile",
+            (Language::English, PowerCard::PushTwice) => "Push twice",
+            (Language::English, PowerCard::RotateFixed) => "Rotate a fixed tile (click)",
+            (Language::English, PowerCard::SwapPlaces) => "Swap with an opponent (click)",
+            (Language::English, PowerCard::WallPass) => "Walk through a wall",
+            (Language::English, PowerCard::BlockSlot) => "Block the spare tile's slot",
+        }
+    }
+}
+
+// The power cards of every player
+#[derive(Resource, Debug, Default)]
+pub struct PowerCards {
+    hands: HashMap<i32, Vec<PowerCard>>,
+    // The card played by the current player, as (player id, index in their hand)
+    // It stays in their hand until its effect happens
+    armed: Option<(i32, usize)>,
+    // How many treasures of each player were already rewarded with a card
+    rewarded: HashMap<i32, usize>,
+    // The round of the last card dealt to everyone
+    dealt_round: u32,
+}
+
+impl PowerCards {
+    pub fn armed(&self) -> Option<PowerCard> {
+        let (player_id, index) = self.armed?;
+        self.hands.get(&player_id)?.get(index).copied()
+    }
+
+    // Use up the armed card
+    pub fn consume(&mut self) {
+        if let Some((player_id, index)) = self.armed.take() {
+            if let Some(hand) = self.hands.get_mut(&player_id) {
+                hand.remove(index);
+            }
+        }
+    }
+
+    fn draw(&mut self, player_id: i32, rng: &mut GameRng) {
+        let hand = self.hands.entry(player_id).or_default();
+        if hand.len() < HAND_SIZE {
+            hand.push(*ALL_CARDS.choose(&mut rng.powers).unwrap());
+        }
+    }
+}
+
+// The board position under the mouse cursor
+#[derive(SystemParam)]
+pub struct BoardCursor<'w, 's> {
+    windows: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    cameras: Query<'w, 's, (&'static Camera, &'static GlobalTransform)>,
+    card_buttons: Query<'w, 's, &'static Interaction, With<CardButton>>,
+    selected_board: Res<'w, SelectedBoard>,
+}
+
+impl BoardCursor<'_, '_> {
+    pub fn grid_position(&self) -> Option<GridPosition> {
+        if self
+            .card_buttons
+            .iter()
+            .any(|interaction| *interaction != Interaction::None)
+        {
+            // the cursor is on the cards, not on the board
+            return None;
+        }
+        let cursor = self.windows.get_single().ok()?.cursor_position()?;
+        let (camera, camera_transform) = self.cameras.get_single().ok()?;
+        let world = camera.viewport_to_world_2d(camera_transform, cursor)?;
+        Some(self.selected_board.board.topology.cell_at(world))
+    }
+}
+
+#[derive(Component, Debug)]
+struct CardPanel;
+
+#[derive(Component, Debug)]
+struct CardButton {
+    index: usize,
+}
+
+pub struct PowersPlugin;
+
+impl Plugin for PowersPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PowerCards::default())
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_power_cards, spawn_card_panel),
+            )
+            .add_systems(
+                Update,
+                (
+                    deal_power_cards,
+                    disarm_on_turn_change,
+                    (
+                        play_power_cards,
+                        block_slot::block_spare_slot,
+                        rotate_fixed::rotate_clicked_tile,
+                        swap_places::swap_with_clicked_opponent,
+                        // a push is only repeated once it happened
+                        push_twice::repeat_push.after(trigger_push),
+                    )
+                        .chain()
+                        .run_if(turn_is_handed_over)
+                        .run_if(human_is_playing),
+                    block_slot::unblock_slots,
+                    update_card_panel,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnExit(GameState::Playing),
+                (cleanup_card_panel, block_slot::clear_blocked_slots),
+            );
+    }
+}
+
+fn reset_power_cards(mut power_cards: ResMut<PowerCards>, game_state: Res<GamePhase>) {
+    *power_cards = PowerCards {
+        dealt_round: game_state.round,
+        ..default()
+    };
+}
+
+fn deal_power_cards(
+    mut power_cards: ResMut<PowerCards>,
+    mut game_rng: ResMut<GameRng>,
+    collected_lists: Res<CollectedLists>,
+    game_settings: Res<GameSettings>,
+    game_state: Res<GamePhase>,
+) {
+    if !game_settings.power_cards || game_state.has_ended {
+        return;
+    }
+    // A card for every collected treasure
+    for id in 0..game_settings.num_players {
+        let collected = collected_lists.lists.get(&id).map_or(0, |list| list.len());
+        let rewarded = power_cards.rewarded.get(&id).copied().unwrap_or(0);
+        for _ in rewarded..collected {
+            power_cards.draw(id, &mut game_rng);
+        }
+        power_cards.rewarded.insert(id, collected);
+    }
+    // And a card for everyone every few rounds
+    if game_state.round >= power_cards.dealt_round + CARD_ROUND_INTERVAL {
+        power_cards.dealt_round = game_state.round;
+        for id in 0..game_settings.num_players {
+            power_cards.draw(id, &mut game_rng);
+        }
+    }
+}
+
+fn disarm_on_turn_change(mut power_cards: ResMut<PowerCards>, game_state: Res<GamePhase>) {
+    // Unused cards go back to the hand at the end of the turn
+    if matches!(power_cards.armed, Some((player_id, _)) if player_id != game_state.current_player_id)
+    {
+        power_cards.armed = None;
+    }
+}
+
+fn play_power_cards(
+    mut power_cards: ResMut<PowerCards>,
+    button_query: Query<(&Interaction, &CardButton), Changed<Interaction>>,
+    game_state: Res<GamePhase>,
+    keys: Res<Input<KeyCode>>,
+    mut sounds: EventWriter<SoundEffect>,
+) {
+    // Play a card with its number key or by clicking it, play it again to take it back
+    let clicked = button_query
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, button)| button.index);
+    let Some(index) = clicked.or_else(|| CARD_KEYS.iter().position(|&key| keys.just_pressed(key)))
+    else {
+        return;
+    };
+
+    let player_id = game_state.current_player_id;
+    let Some(&card) = power_cards
+        .hands
+        .get(&player_id)
+        .and_then(|hand| hand.get(index))
+    else {
+        return;
+    };
+
+    if power_cards.armed == Some((player_id, index)) {
+        power_cards.armed = None;
+    } else if card.in_push_phase() == game_state.tile_push_phase && !game_state.has_ended {
+        power_cards.armed = Some((player_id, index));
+    } else {
+        // Not the right time for this card
+        sounds.send(SoundEffect::IllegalPush);
+    }
+}
+
+fn spawn_card_panel(
+    mut commands: Commands,
+    game_settings: Res<GameSettings>,
+    theme: Res<ChosenTheme>,
+) {
+    if !game_settings.power_cards {
+        return;
+    }
+    let colors = theme.get().colors;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::VMin(1.0),
+                    right: Val::VMax(0.5),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            },
+            CardPanel,
+        ))
+        .with_children(|panel| {
+            for index in 0..HAND_SIZE {
+                panel
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(6.0)),
+                                ..default()
+                            },
+                            background_color: colors.card.into(),
+                            ..default()
+                        },
+                        CardButton { index },
+                    ))
+                    .with_children(|button| {
+                        button.spawn((
+                            TextBundle::from_section(
+                                "",
+                                TextStyle {
+                                    font_size: FONT_SIZE,
+                                    color: colors.text,
+                                    ..default()
+                                },
+                            ),
+                            ThemeColor::Text,
+                        ));
+                    });
+            }
+        });
+}
+
+fn update_card_panel(
+    mut button_query: Query<(&CardButton, &mut Style, &mut BackgroundColor, &Children)>,
+    mut text_query: Query<&mut Text>,
+    power_cards: Res<PowerCards>,
+    game_state: Res<GamePhase>,
+    hand_over: Res<HandOver>,
+    language: Res<ChosenLanguage>,
+    theme: Res<ChosenTheme>,
+) {
+    // Only the player holding the device sees their cards
+    let hand = power_cards
+        .hands
+        .get(&game_state.current_player_id)
+        .filter(|_| hand_over.confirmed && !game_state.has_ended);
+
+    for (button, mut style, mut background, children) in &mut button_query {
+        let Some(card) = hand.and_then(|hand| hand.get(button.index)) else {
+            style.display = Display::None;
+            continue;
+        };
+        style.display = Display::Flex;
+        *background = if power_cards.armed == Some((game_state.current_player_id, button.index)) {
+            theme.get().colors.armed_card.into()
+        } else {
+            theme.get().colors.card.into()
+        };
+        for &child in children {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value =
+                    format!("{}: {}", button.index + 1, card.name(&language.lang));
+            }
+        }
+    }
+}
+
+fn cleanup_card_panel(mut commands: Commands, panel_query: Query<Entity, With<CardPanel>>) {
+    for entity in &panel_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
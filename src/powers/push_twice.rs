@@ -0,0 +1,16 @@
+use bevy::prelude::*;
+
+use super::{PowerCard, PowerCards};
+use crate::GamePhase;
+
+// Played while pushing: once the tile is pushed, the player pushes again
+// The usual rule applies to the second push, it cannot undo the first one
+pub fn repeat_push(mut power_cards: ResMut<PowerCards>, mut game_state: ResMut<GamePhase>) {
+    if power_cards.armed() == Some(PowerCard::PushTwice)
+        && !game_state.tile_push_phase
+        && !game_state.has_ended
+    {
+        game_state.tile_push_phase = true;
+        power_cards.consume();
+    }
+}
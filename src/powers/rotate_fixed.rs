@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+
+use super::{BoardCursor, PowerCard, PowerCards};
+use crate::{
+    movement::CanMove,
+    sound::SoundEffect,
+    tile::{Orientation, TileType},
+    GridPosition,
+};
+
+// Played while pushing: click a tile that cannot be pushed to turn it a quarter clockwise
+pub fn rotate_clicked_tile(
+    mut tiles_query: Query<(&GridPosition, &CanMove, &mut Orientation), With<TileType>>,
+    mut power_cards: ResMut<PowerCards>,
+    buttons: Res<Input<MouseButton>>,
+    cursor: BoardCursor,
+    mut sounds: EventWriter<SoundEffect>,
+) {
+    if power_cards.armed() != Some(PowerCard::RotateFixed)
+        || !buttons.just_pressed(MouseButton::Left)
+    {
+        return;
+    }
+    let Some(clicked) = cursor.grid_position() else {
+        return;
+    };
+
+    for (grid_pos, can_move, mut orientation) in &mut tiles_query {
+        if *grid_pos == clicked {
+            if matches!(can_move, CanMove::No) {
+                *orientation = orientation.rotated_clockwise();
+                sounds.send(SoundEffect::TileRotate);
+                power_cards.consume();
+            } else {
+                sounds.send(SoundEffect::IllegalPush);
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+
+use super::{BoardCursor, PowerCard, PowerCards};
+use crate::{
+    movement::BridgeLevel, player::Player, sound::SoundEffect, GamePhase, GameSettings,
+    GridPosition,
+};
+
+// Played while moving: click the tile of an opponent to swap places with them
+// Teammates are not opponents, clicking one is refused like clicking an empty tile
+pub fn swap_with_clicked_opponent(
+    mut player_query: Query<(
+        Entity,
+        &Player,
+        &mut GridPosition,
+        &mut Transform,
+        &mut BridgeLevel,
+    )>,
+    mut power_cards: ResMut<PowerCards>,
+    game_state: Res<GamePhase>,
+    game_settings: Res<GameSettings>,
+    buttons: Res<Input<MouseButton>>,
+    cursor: BoardCursor,
+    mut sounds: EventWriter<SoundEffect>,
+) {
+    if power_cards.armed() != Some(PowerCard::SwapPlaces)
+        || !buttons.just_pressed(MouseButton::Left)
+    {
+        return;
+    }
+    let Some(clicked) = cursor.grid_position() else {
+        return;
+    };
+
+    let current = player_query
+        .iter()
+        .find(|(_, player, ..)| player.id == game_state.current_player_id)
+        .map(|(entity, ..)| entity);
+    let teams = game_settings.teams;
+    let current_team = teams.team_of(game_state.current_player_id);
+    let opponent = player_query
+        .iter()
+        .find(|(_, player, grid_pos, ..)| {
+            teams.team_of(player.id) != current_team && **grid_pos == clicked
+        })
+        .map(|(entity, ..)| entity);
+
+    let (Some(current), Some(opponent)) = (current, opponent) else {
+        sounds.send(SoundEffect::IllegalPush);
+        return;
+    };
+    if let Ok([mut first, mut second]) = player_query.get_many_mut([current, opponent]) {
+        std::mem::swap(&mut *first.2, &mut *second.2);
+        std::mem::swap(&mut first.3.translation, &mut second.3.translation);
+        std::mem::swap(&mut *first.4, &mut *second.4);
+        sounds.send(SoundEffect::Footstep);
+        power_cards.consume();
+    }
+}
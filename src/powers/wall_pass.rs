@@ -0,0 +1,26 @@
+use super::{PowerCard, PowerCards};
+use crate::{
+    board::Board,
+    movement::{get_neighbour_pos, pos_is_external, BridgeLevel, Direction},
+    GridPosition,
+};
+
+// Played while moving: the next step blocked by a wall goes through it
+// Steps through open passages don't use the card
+pub fn step_through_wall(
+    power_cards: &mut PowerCards,
+    from: GridPosition,
+    dir: &Direction,
+    board: &Board,
+) -> Option<(GridPosition, BridgeLevel)> {
+    if power_cards.armed() != Some(PowerCard::WallPass) {
+        return None;
+    }
+    let destination = get_neighbour_pos(&from, dir);
+    if pos_is_external(&destination, board) {
+        // the edges and holes of the board are not walls
+        return None;
+    }
+    power_cards.consume();
+    Some((destination, BridgeLevel::Over))
+}
@@ -0,0 +1,228 @@
+use std::fmt;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    reflect::TypePath,
+    utils::BoxedFuture,
+};
+use ndarray::Array2;
+use serde::Deserialize;
+
+use crate::{
+    board::{Board, TileInfo},
+    movement::CanMove,
+    puzzles::{AvailablePuzzles, Puzzle, PuzzleGoal},
+    tile::{Orientation, TileType},
+    tile_registry::TILE_REGISTRY,
+    topology::Topology,
+    GridPosition,
+};
+
+// The hand-made puzzles, in the assets folder
+// Read through the asset server like the treasure catalogue, and straight from the file by the
+// solver binary
+pub const PUZZLES_PATH: &str = "puzzles/base.puzzles.ron";
+
+// Puzzle boards only have set tiles, so that a puzzle is the same every time
+#[derive(Debug, Deserialize)]
+enum PuzzleTile {
+    Fixed(String, i32),
+    Movable(String, i32),
+}
+
+#[derive(Debug, Deserialize)]
+struct PuzzleDefinition {
+    name: String,
+    // from the top row down
+    rows: Vec<Vec<PuzzleTile>>,
+    spare: PuzzleTile,
+    start: (i32, i32),
+    target: (i32, i32),
+    par: u32,
+    #[serde(default)]
+    topology: Topology,
+}
+
+#[derive(Asset, TypePath, Debug)]
+pub struct PuzzleList(Vec<Puzzle>);
+
+#[derive(Debug)]
+pub enum PuzzleError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PuzzleError::Io(error) => write!(f, "could not read the puzzles: {error}"),
+            PuzzleError::Ron(error) => write!(f, "invalid puzzles: {error}"),
+            PuzzleError::Invalid(error) => write!(f, "invalid puzzle: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for PuzzleError {}
+
+impl From<std::io::Error> for PuzzleError {
+    fn from(error: std::io::Error) -> Self {
+        PuzzleError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for PuzzleError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        PuzzleError::Ron(error)
+    }
+}
+
+impl PuzzleTile {
+    fn to_tile(&self, puzzle: &str) -> Result<TileInfo, PuzzleError> {
+        let (name, turns, can_move) = match self {
+            PuzzleTile::Fixed(name, turns) => (name, *turns, CanMove::No),
+            PuzzleTile::Movable(name, turns) => (name, *turns, CanMove::Yes),
+        };
+        let Some(index) = TILE_REGISTRY.index_of(name) else {
+            return Err(PuzzleError::Invalid(format!(
+                "{puzzle} has an unknown tile kind {name}"
+            )));
+        };
+        Ok(TileInfo {
+            tile_type: TileType::Kind(index),
+            orientation: Orientation::new(turns),
+            can_move,
+        })
+    }
+}
+
+impl PuzzleDefinition {
+    fn to_puzzle(&self) -> Result<Puzzle, PuzzleError> {
+        let name = &self.name;
+        let width = self.rows.first().map_or(0, Vec::len);
+        if width == 0 || self.rows.iter().any(|row| row.len() != width) {
+            return Err(PuzzleError::Invalid(format!(
+                "the rows of {name} are empty or not all of the same length"
+            )));
+        }
+        let tiles = self
+            .rows
+            .iter()
+            .flatten()
+            .map(|tile| tile.to_tile(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let board = Board {
+            tiles: Array2::from_shape_vec((self.rows.len(), width), tiles)
+                .expect("the rows have the same length"),
+            spawn_positions: vec![],
+            treasure_positions: vec![],
+            external_tiles: vec![self.spare.to_tile(name)?],
+            tile_deck: vec![],
+            topology: self.topology,
+        };
+        let cell = |(x_pos, y_pos): (i32, i32)| GridPosition { x_pos, y_pos };
+        let (start, target) = (cell(self.start), cell(self.target));
+        if !board.contains(&start) || !board.contains(&target) {
+            return Err(PuzzleError::Invalid(format!(
+                "the start or the target of {name} is outside of the board"
+            )));
+        }
+        Ok(Puzzle {
+            name: name.clone(),
+            seed: None,
+            board,
+            goal: PuzzleGoal {
+                start,
+                target,
+                par: self.par,
+            },
+        })
+    }
+}
+
+pub fn parse_puzzles(bytes: &[u8]) -> Result<Vec<Puzzle>, PuzzleError> {
+    let definitions: Vec<PuzzleDefinition> = ron::de::from_bytes(bytes)?;
+    let puzzles = definitions
+        .iter()
+        .map(PuzzleDefinition::to_puzzle)
+        .collect::<Result<Vec<_>, _>>()?;
+    // the scores are kept by name
+    for (index, puzzle) in puzzles.iter().enumerate() {
+        if puzzles[..index]
+            .iter()
+            .any(|other| other.name == puzzle.name)
+        {
+            return Err(PuzzleError::Invalid(format!(
+                "{} is declared twice",
+                puzzle.name
+            )));
+        }
+    }
+    Ok(puzzles)
+}
+
+// For the solver binary, run from the folder of the game like the game itself
+pub fn read_puzzles() -> Result<Vec<Puzzle>, PuzzleError> {
+    parse_puzzles(&std::fs::read(format!("assets/{PUZZLES_PATH}"))?)
+}
+
+#[derive(Default)]
+struct PuzzleListLoader;
+
+impl AssetLoader for PuzzleListLoader {
+    type Asset = PuzzleList;
+    type Settings = ();
+    type Error = PuzzleError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<PuzzleList, PuzzleError>> {
+        Box::pin(async move {
+            let mut bytes = vec![];
+            reader.read_to_end(&mut bytes).await?;
+            Ok(PuzzleList(parse_puzzles(&bytes)?))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["puzzles.ron"]
+    }
+}
+
+#[derive(Resource, Debug)]
+struct PuzzleListHandle(Handle<PuzzleList>);
+
+pub struct PuzzleCataloguePlugin;
+
+impl Plugin for PuzzleCataloguePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<PuzzleList>()
+            .init_asset_loader::<PuzzleListLoader>()
+            .add_systems(Startup, load_puzzles)
+            .add_systems(Update, update_hand_made_puzzles);
+    }
+}
+
+fn load_puzzles(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(PuzzleListHandle(asset_server.load(PUZZLES_PATH)));
+}
+
+fn update_hand_made_puzzles(
+    mut events: EventReader<AssetEvent<PuzzleList>>,
+    puzzle_lists: Res<Assets<PuzzleList>>,
+    handle: Res<PuzzleListHandle>,
+    mut available: ResMut<AvailablePuzzles>,
+) {
+    // Once loaded, and again whenever the file changes
+    for event in events.read() {
+        if event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0) {
+            if let Some(puzzles) = puzzle_lists.get(&handle.0) {
+                available.hand_made = puzzles.0.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,725 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use once_cell::sync::Lazy;
+use rand::{seq::IteratorRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actors::SpawnPosition,
+    background::Background,
+    board::{deal_tiles, Board},
+    board_selector::SelectedBoard,
+    boards_repository::BOARD_0,
+    movement::{BridgeLevel, IllegalPushPositions, PushSlots},
+    phases::GameState,
+    player::Player,
+    puzzle_catalogue::read_puzzles,
+    rng::GameSeed,
+    simulation::arg_value,
+    solver::{solve, SearchLimits, SolutionTurn, SolveFailure, SolverState, SolverTilesQuery},
+    teams::Teams,
+    theme::{ChosenTheme, ThemeColor},
+    tile::{Orientation, TileType},
+    treasure::Treasure,
+    ui::{ChosenLanguage, Language},
+    GamePhase, GameSettings, GridPosition,
+};
+
+// Where the best scores are kept between sessions
+const SCORES_PATH: &str = "puzzle_scores.ron";
+const FONT_SIZE: f32 = 28.0;
+// The generated puzzles are solved while the menu is open, and a game may wait for one,
+// so the search must stay short
+const GENERATION_LIMITS: SearchLimits = SearchLimits {
+    max_turns: 3,
+    max_states: 20_000,
+};
+const GENERATION_ATTEMPTS: usize = 20;
+const TARGETS_PER_BOARD: usize = 3;
+
+// A single player challenge: reach the treasure in as few turns as possible
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    // Identifies the puzzle in the saved scores
+    pub name: String,
+    // The seed the puzzle was generated from, if it is not hand-made
+    pub seed: Option<u64>,
+    // Only set tiles, the spawn and treasure positions come from the goal
+    pub board: Board,
+    pub goal: PuzzleGoal,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PuzzleGoal {
+    pub start: GridPosition,
+    pub target: GridPosition,
+    // The number of turns of a good solution
+    pub par: u32,
+}
+
+impl Puzzle {
+    // The board to play, with the player and the treasure in place
+    pub fn board(&self) -> Board {
+        Board {
+            spawn_positions: vec![SpawnPosition::Position(self.goal.start)],
+            treasure_positions: vec![SpawnPosition::Position(self.goal.target)],
+            ..self.board.clone()
+        }
+    }
+
+    // Three stars at par or under, two up to two turns over it
+    pub fn stars(&self, turns: u32) -> u32 {
+        match turns.saturating_sub(self.goal.par) {
+            0 => 3,
+            1 | 2 => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn title(&self, language: &Language) -> String {
+        match (self.seed, language) {
+            (None, _) => self.name.clone(),
+            (Some(seed), Language::French) => format!("Aleatoire (graine {seed})"),
+            (Some(seed), Language::English) => format!("Random (seed {seed})"),
+        }
+    }
+}
+
+// None in the very unlikely case that no board dealt from the seed has a good puzzle
+pub fn generate_puzzle(seed: u64) -> Option<Puzzle> {
+    // Board 0 dealt again, with its plain tiles, until the solver finds a treasure far from
+    // the start, that takes at least two turns to reach
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let layout = Lazy::force(&BOARD_0);
+    let start = GridPosition { x_pos: 0, y_pos: 0 };
+
+    for _ in 0..GENERATION_ATTEMPTS {
+        let board = deal_tiles(layout, &mut rng);
+        let (max_x, max_y) = board.max_coords();
+        let targets = (0..=max_y)
+            .flat_map(|y_pos| (0..=max_x).map(move |x_pos| GridPosition { x_pos, y_pos }))
+            .filter(|pos| board.topology.distance(&start, pos) >= 4)
+            .choose_multiple(&mut rng, TARGETS_PER_BOARD);
+        for target in targets {
+            let state = SolverState::from_board(&board, start, target);
+            if let Ok(solution) = solve(&board, &state, GENERATION_LIMITS) {
+                if solution.len() >= 2 {
+                    return Some(Puzzle {
+                        name: format!("generated-{seed}"),
+                        seed: Some(seed),
+                        board,
+                        goal: PuzzleGoal {
+                            start,
+                            target,
+                            par: solution.len() as u32,
+                        },
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+// The hand-made puzzles, then the one generated from the seed of the next game
+// Generating a puzzle runs the solver many times, so it is only done in puzzle mode, in the
+// background, and again when the seed changes
+#[derive(Resource, Debug, Default)]
+pub struct AvailablePuzzles {
+    // filled from the puzzles asset once it is loaded
+    pub hand_made: Vec<Puzzle>,
+    // the seed the generated puzzle is for
+    seed: Option<u64>,
+    generated: Option<Puzzle>,
+    generating: Option<Background<Option<Puzzle>>>,
+}
+
+impl AvailablePuzzles {
+    fn len(&self) -> usize {
+        self.hand_made.len() + 1
+    }
+
+    // None for the generated puzzle until it is ready
+    fn get(&self, index: usize) -> Option<&Puzzle> {
+        self.hand_made.get(index).or(self
+            .generated
+            .as_ref()
+            .filter(|_| index == self.hand_made.len()))
+    }
+
+    fn generate(&mut self, seed: u64) {
+        // The puzzle of an earlier seed is dropped, even if it is not done
+        if self.seed != Some(seed) {
+            self.seed = Some(seed);
+            self.generated = None;
+            self.generating = Some(Background::spawn(move || generate_puzzle(seed)));
+        }
+    }
+
+    fn poll(&mut self) {
+        if let Some(generating) = self.generating.take() {
+            match generating.poll() {
+                Ok(puzzle) => self.generated = puzzle.or_else(|| self.fallback()),
+                Err(generating) => self.generating = Some(generating),
+            }
+        }
+    }
+
+    fn wait(&mut self) {
+        if let Some(generating) = self.generating.take() {
+            self.generated = generating.wait().or_else(|| self.fallback());
+        }
+    }
+
+    // A puzzle must be offered even when none could be generated
+    fn fallback(&self) -> Option<Puzzle> {
+        self.hand_made.first().cloned()
+    }
+}
+
+pub fn solve_from_args() {
+    // Print the solutions of the `--puzzle <puzzle name, seed or all>` puzzles, all by default
+    let args = std::env::args().collect::<Vec<_>>();
+    let wanted = arg_value(&args, "--puzzle").unwrap_or("all".to_string());
+
+    let puzzles: Vec<Puzzle> = match wanted.parse::<u64>() {
+        Ok(seed) => generate_puzzle(seed).into_iter().collect(),
+        Err(_) => match read_puzzles() {
+            Ok(puzzles) => puzzles
+                .into_iter()
+                .filter(|puzzle| wanted == "all" || puzzle.name.eq_ignore_ascii_case(&wanted))
+                .collect(),
+            Err(error) => {
+                eprintln!("{error}");
+                std::process::exit(1);
+            }
+        },
+    };
+    if puzzles.is_empty() {
+        eprintln!("Unknown puzzle: {wanted}");
+        std::process::exit(1);
+    }
+    for puzzle in puzzles {
+        let board = puzzle.board();
+        let state = SolverState::from_board(&board, puzzle.goal.start, puzzle.goal.target);
+        let limits = SearchLimits {
+            max_turns: 6,
+            max_states: 2_000_000,
+        };
+        println!(
+            "{} (par {})",
+            puzzle.title(&Language::English),
+            puzzle.goal.par
+        );
+        match solve(&board, &state, limits) {
+            Ok(solution) => {
+                for (index, turn) in solution.iter().enumerate() {
+                    println!(
+                        "  {}. {}",
+                        index + 1,
+                        describe_turn(&board, turn, &Language::English)
+                    );
+                }
+            }
+            Err(failure) => println!("  No solution found: {failure:?}"),
+        }
+    }
+}
+
+pub fn describe_turn(board: &Board, turn: &SolutionTurn, language: &Language) -> String {
+    let slots = board.push_slots();
+    let Some(slot) = slots.get(turn.slot) else {
+        return String::new();
+    };
+    let cell = |pos: &GridPosition| format!("({}, {})", pos.x_pos, pos.y_pos);
+    let path = turn.path.iter().map(cell).collect::<Vec<_>>().join(" ");
+    match language {
+        Language::French => format!(
+            "R x{}, pousser vers {} depuis {}, marcher: {}",
+            turn.clockwise_turns,
+            slot.dir.name(language),
+            cell(&slot.pos),
+            if path.is_empty() { "-" } else { &path }
+        ),
+        Language::English => format!(
+            "R x{}, push {} from {}, walk: {}",
+            turn.clockwise_turns,
+            slot.dir.name(language),
+            cell(&slot.pos),
+            if path.is_empty() { "-" } else { &path }
+        ),
+    }
+}
+
+// The puzzle picked in the menu, and the normal game it stands in for
+#[derive(Resource, Debug, Default)]
+pub struct PuzzleMode {
+    // index in the available puzzles, None for a normal game
+    selected: Option<usize>,
+    // the puzzle being played
+    playing: Option<Puzzle>,
+    // the board and settings of the normal game, put back after the puzzle
+    saved: Option<(Board, GameSettings)>,
+    // whether the score of the finished puzzle was recorded
+    recorded: bool,
+    // the solution asked for, with the round it was found in
+    hint: Option<(u32, String)>,
+    // the solution being looked for in the background, with the round it is for
+    solving: Option<(u32, Background<Solution>)>,
+}
+
+type Solution = Result<Vec<SolutionTurn>, SolveFailure>;
+
+// The fewest turns each puzzle was solved in, by puzzle name
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+pub struct PuzzleScores {
+    best: BTreeMap<String, u32>,
+}
+
+impl PuzzleScores {
+    fn load() -> Self {
+        // There is no file system on the web, scores only last for the session there
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(scores) = std::fs::read_to_string(SCORES_PATH)
+            .ok()
+            .and_then(|text| ron::from_str(&text).ok())
+        {
+            return scores;
+        }
+        PuzzleScores::default()
+    }
+
+    fn save(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(text) => {
+                if let Err(error) = std::fs::write(SCORES_PATH, text) {
+                    warn!("Could not save the puzzle scores: {error}");
+                }
+            }
+            Err(error) => warn!("Could not serialize the puzzle scores: {error}"),
+        }
+    }
+}
+
+// The menu line describing the selected puzzle
+pub fn puzzle_menu_text(
+    mode: &PuzzleMode,
+    available: &AvailablePuzzles,
+    scores: &PuzzleScores,
+    language: &Language,
+) -> String {
+    let Some(index) = mode.selected else {
+        return match language {
+            Language::French => "P: Puzzle solo: non".to_string(),
+            Language::English => "P: Solo puzzle: off".to_string(),
+        };
+    };
+    let Some(puzzle) = available.get(index) else {
+        let seed = available.seed.unwrap_or_default();
+        return match (available.generating.is_some(), language) {
+            (true, Language::French) => {
+                format!("P: Puzzle: Aleatoire (graine {seed}), generation...")
+            }
+            (true, Language::English) => format!("P: Puzzle: Random (seed {seed}), generating..."),
+            (false, Language::French) => "P: Puzzle: aucun puzzle disponible".to_string(),
+            (false, Language::English) => "P: Puzzle: no puzzle available".to_string(),
+        };
+    };
+    let best = scores
+        .best
+        .get(&puzzle.name)
+        .map_or("-".to_string(), |turns| turns.to_string());
+    match language {
+        Language::French => format!(
+            "P: Puzzle: {} (par {}, record: {best})",
+            puzzle.title(language),
+            puzzle.goal.par
+        ),
+        Language::English => format!(
+            "P: Puzzle: {} (par {}, best: {best})",
+            puzzle.title(language),
+            puzzle.goal.par
+        ),
+    }
+}
+
+#[derive(Component, Debug)]
+struct PuzzlePanel;
+
+#[derive(Component, Debug)]
+struct PuzzleText;
+
+#[derive(Component, Debug)]
+struct SolutionButton;
+
+pub struct PuzzlesPlugin;
+
+impl Plugin for PuzzlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PuzzleMode::default())
+            .insert_resource(PuzzleScores::load())
+            .insert_resource(AvailablePuzzles::default())
+            .add_systems(
+                Update,
+                (cycle_puzzles, generate_seeded_puzzle)
+                    .chain()
+                    .run_if(in_state(GameState::MainMenu)),
+            )
+            // Leaving the menu happens before the board and the players are spawned
+            .add_systems(OnExit(GameState::MainMenu), start_puzzle)
+            .add_systems(
+                OnEnter(GameState::Playing),
+                spawn_puzzle_panel.run_if(puzzle_is_playing),
+            )
+            .add_systems(
+                Update,
+                (
+                    record_score,
+                    show_solution,
+                    receive_solution,
+                    update_puzzle_panel,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(puzzle_is_playing),
+            )
+            .add_systems(OnExit(GameState::Playing), finish_puzzle);
+    }
+}
+
+fn puzzle_is_playing(mode: Res<PuzzleMode>) -> bool {
+    mode.playing.is_some()
+}
+
+fn generate_seeded_puzzle(
+    mut available: ResMut<AvailablePuzzles>,
+    mode: Res<PuzzleMode>,
+    seed: Res<GameSeed>,
+) {
+    if mode.selected.is_some() {
+        available.generate(seed.value);
+    }
+    available.poll();
+}
+
+fn cycle_puzzles(
+    mut mode: ResMut<PuzzleMode>,
+    available: Res<AvailablePuzzles>,
+    keys: Res<Input<KeyCode>>,
+) {
+    // Go through the puzzles, and back to a normal game after the last one
+    if keys.just_pressed(KeyCode::P) {
+        let num_puzzles = available.len();
+        mode.selected = match mode.selected {
+            None => Some(0),
+            Some(index) if index + 1 < num_puzzles => Some(index + 1),
+            Some(_) => None,
+        };
+    }
+}
+
+fn start_puzzle(
+    mut mode: ResMut<PuzzleMode>,
+    mut selected_board: ResMut<SelectedBoard>,
+    mut game_settings: ResMut<GameSettings>,
+    mut available: ResMut<AvailablePuzzles>,
+    seed: Res<GameSeed>,
+) {
+    let Some(index) = mode.selected else {
+        return;
+    };
+    // The seed may have been typed on the frame the game was started, or the generated puzzle
+    // may not be done yet: the game can't start without it
+    available.generate(seed.value);
+    if available.get(index).is_none() {
+        available.wait();
+    }
+    let Some(puzzle) = available.get(index).cloned() else {
+        return;
+    };
+    // A lone player, after a single treasure, with none of the variants
+    mode.saved = Some((selected_board.board.clone(), game_settings.clone()));
+    selected_board.board = puzzle.board();
+    *game_settings = GameSettings {
+        num_players: 1,
+        treasures_to_get: 1,
+        hot_seat: false,
+        power_cards: false,
+        fog_of_war: false,
+        teams: Teams::Solo,
+    };
+    mode.playing = Some(puzzle);
+    mode.recorded = false;
+    mode.hint = None;
+    mode.solving = None;
+}
+
+fn record_score(
+    mut mode: ResMut<PuzzleMode>,
+    mut scores: ResMut<PuzzleScores>,
+    game_state: Res<GamePhase>,
+) {
+    if !game_state.has_ended || mode.recorded {
+        return;
+    }
+    // Each turn of the lone player is a round
+    let turns = game_state.round;
+    if let Some(puzzle) = &mode.playing {
+        let best = scores.best.entry(puzzle.name.clone()).or_insert(turns);
+        *best = (*best).min(turns);
+        scores.save();
+    }
+    mode.recorded = true;
+}
+
+fn spawn_puzzle_panel(
+    mut commands: Commands,
+    language: Res<ChosenLanguage>,
+    theme: Res<ChosenTheme>,
+) {
+    let colors = theme.get().colors;
+    let button_label = match language.lang {
+        Language::French => "Voir la solution (H)",
+        Language::English => "Show the solution (H)",
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::VMax(0.5),
+                    bottom: Val::VMin(1.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                background_color: colors.panel.into(),
+                ..default()
+            },
+            PuzzlePanel,
+            ThemeColor::Panel,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: FONT_SIZE,
+                        color: colors.text,
+                        ..default()
+                    },
+                ),
+                PuzzleText,
+                ThemeColor::Text,
+            ));
+            panel
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(4.0)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        background_color: colors.button.into(),
+                        ..default()
+                    },
+                    SolutionButton,
+                    ThemeColor::Button,
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        TextBundle::from_section(
+                            button_label,
+                            TextStyle {
+                                font_size: FONT_SIZE * 0.7,
+                                color: colors.text,
+                                ..default()
+                            },
+                        ),
+                        ThemeColor::Text,
+                    ));
+                });
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn show_solution(
+    mut mode: ResMut<PuzzleMode>,
+    button_query: Query<&Interaction, (Changed<Interaction>, With<SolutionButton>)>,
+    tiles_query: SolverTilesQuery,
+    player_query: Query<(&GridPosition, &BridgeLevel), With<Player>>,
+    treasure_query: Query<&GridPosition, With<Treasure>>,
+    selected_board: Res<SelectedBoard>,
+    push_slots: Res<PushSlots>,
+    illegal: Res<IllegalPushPositions>,
+    game_state: Res<GamePhase>,
+    language: Res<ChosenLanguage>,
+    keys: Res<Input<KeyCode>>,
+) {
+    let pressed = button_query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    if !(pressed || keys.just_pressed(KeyCode::H)) || game_state.has_ended || mode.solving.is_some()
+    {
+        return;
+    }
+    // The search starts from a whole turn, so only before the push
+    let hint = if !game_state.tile_push_phase {
+        match language.lang {
+            Language::French => "Disponible au debut d'un tour".to_string(),
+            Language::English => "Available at the start of a turn".to_string(),
+        }
+    } else if let (Some(spare_pos), Ok((player_pos, player_level))) = (
+        push_slots
+            .slots
+            .get(push_slots.current)
+            .map(|slot| slot.pos),
+        player_query.get_single(),
+    ) {
+        let board = &selected_board.board;
+        let spare = tiles_query
+            .iter()
+            .find(|(pos, ..)| **pos == spare_pos)
+            .map_or(
+                (TileType::Void, Orientation::default()),
+                |(_, tile_type, orientation)| (*tile_type, *orientation),
+            );
+        // Out of the board, the treasure is on the spare tile
+        let treasure = treasure_query
+            .iter()
+            .next()
+            .copied()
+            .filter(|pos| board.contains(pos));
+        let state = SolverState::new(
+            board,
+            tiles_query
+                .iter()
+                .map(|(pos, tile_type, orientation)| (*pos, *tile_type, *orientation)),
+            spare,
+            (*player_pos, *player_level),
+            treasure,
+            (0..push_slots.slots.len()).find(|&slot| illegal.is_illegal(slot)),
+        );
+        // The search can take a while, the game keeps drawing meanwhile
+        let board = board.clone();
+        mode.solving = Some((
+            game_state.round,
+            Background::spawn(move || solve(&board, &state, SearchLimits::default())),
+        ));
+        match language.lang {
+            Language::French => "Recherche de la solution...".to_string(),
+            Language::English => "Looking for the solution...".to_string(),
+        }
+    } else {
+        return;
+    };
+    mode.hint = Some((game_state.round, hint));
+}
+
+fn receive_solution(
+    mut mode: ResMut<PuzzleMode>,
+    selected_board: Res<SelectedBoard>,
+    language: Res<ChosenLanguage>,
+) {
+    let Some((round, solving)) = mode.solving.take() else {
+        return;
+    };
+    let solution = match solving.poll() {
+        Ok(solution) => solution,
+        Err(solving) => {
+            mode.solving = Some((round, solving));
+            return;
+        }
+    };
+    let board = &selected_board.board;
+    let hint = match (solution, &language.lang) {
+        (Ok(solution), _) => solution
+            .iter()
+            .enumerate()
+            .map(|(index, turn)| {
+                format!(
+                    "{}. {}",
+                    index + 1,
+                    describe_turn(board, turn, &language.lang)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        (Err(SolveFailure::NoSolution), Language::French) => {
+            "Pas de solution en quelques tours".to_string()
+        }
+        (Err(SolveFailure::NoSolution), Language::English) => {
+            "No solution in a few turns".to_string()
+        }
+        (Err(SolveFailure::TooManyStates), Language::French) => {
+            "Trop de positions a explorer".to_string()
+        }
+        (Err(SolveFailure::TooManyStates), Language::English) => {
+            "Too many positions to look through".to_string()
+        }
+    };
+    mode.hint = Some((round, hint));
+}
+
+fn update_puzzle_panel(
+    mut text_query: Query<&mut Text, With<PuzzleText>>,
+    mode: Res<PuzzleMode>,
+    scores: Res<PuzzleScores>,
+    game_state: Res<GamePhase>,
+    language: Res<ChosenLanguage>,
+) {
+    let Some(puzzle) = &mode.playing else {
+        return;
+    };
+    let title = puzzle.title(&language.lang);
+    let par = puzzle.goal.par;
+    let turns = game_state.round;
+    let best = scores
+        .best
+        .get(&puzzle.name)
+        .map_or("-".to_string(), |turns| turns.to_string());
+    let stars = puzzle.stars(turns);
+    let value = match (&language.lang, game_state.has_ended) {
+        (Language::French, false) => format!("{title}\nTour {} (par {par})", turns + 1),
+        (Language::English, false) => format!("{title}\nTurn {} (par {par})", turns + 1),
+        (Language::French, true) => format!(
+            "{title}\nResolu en {turns} tours (par {par})\nEtoiles: {stars}/3, record: {best}"
+        ),
+        (Language::English, true) => {
+            format!("{title}\nSolved in {turns} turns (par {par})\nStars: {stars}/3, best: {best}")
+        }
+    };
+    // The solution only holds for the turn it was asked in
+    let value = match &mode.hint {
+        Some((round, hint)) if *round == turns && !game_state.has_ended => {
+            format!("{value}\n{hint}")
+        }
+        _ => value,
+    };
+    for mut text in &mut text_query {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn finish_puzzle(
+    mut commands: Commands,
+    panel_query: Query<Entity, With<PuzzlePanel>>,
+    mut mode: ResMut<PuzzleMode>,
+    mut selected_board: ResMut<SelectedBoard>,
+    mut game_settings: ResMut<GameSettings>,
+) {
+    for entity in &panel_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    // Back to the normal game
+    if let Some((board, settings)) = mode.saved.take() {
+        selected_board.board = board;
+        *game_settings = settings;
+    }
+    mode.playing = None;
+    mode.solving = None;
+}
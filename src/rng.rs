@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use rand::{random, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::phases::GameState;
+
+// The seed of the next game
+#[derive(Resource, Debug)]
+pub struct GameSeed {
+    pub value: u64,
+    // whether the seed was chosen by the players, and should be kept for the next games
+    pub chosen: bool,
+}
+
+// All of the randomness of a game, drawn from the GameSeed
+// Each kind of random choice has its own stream, so that the order in which
+// the setup systems run does not change the outcome
+#[derive(Resource, Debug)]
+pub struct GameRng {
+    // type and rotation of the movable tiles
+    pub tiles: ChaCha8Rng,
+    // random player spawns
+    pub spawns: ChaCha8Rng,
+    // random treasure positions
+    pub treasures: ChaCha8Rng,
+    // treasures to find for each player
+    pub deal: ChaCha8Rng,
+    // power cards drawn by the players
+    pub powers: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        GameRng {
+            tiles: ChaCha8Rng::seed_from_u64(seed),
+            spawns: ChaCha8Rng::seed_from_u64(seed.wrapping_add(1)),
+            treasures: ChaCha8Rng::seed_from_u64(seed.wrapping_add(2)),
+            deal: ChaCha8Rng::seed_from_u64(seed.wrapping_add(3)),
+            powers: ChaCha8Rng::seed_from_u64(seed.wrapping_add(4)),
+        }
+    }
+}
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = match seed_from_args() {
+            Some(value) => GameSeed {
+                value,
+                chosen: true,
+            },
+            None => GameSeed {
+                value: random(),
+                chosen: false,
+            },
+        };
+        app.insert_resource(GameRng::from_seed(seed.value))
+            .insert_resource(seed)
+            // Leaving the menu happens before any of the game setup systems run
+            .add_systems(OnExit(GameState::MainMenu), reseed)
+            .add_systems(OnExit(GameState::Playing), draw_next_seed);
+    }
+}
+
+fn seed_from_args() -> Option<u64> {
+    // Read a `--seed <number>` or `--seed=<number>` command line argument
+    let args = std::env::args().collect::<Vec<_>>();
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "--seed" {
+            args.get(i + 1)?.parse().ok()
+        } else {
+            arg.strip_prefix("--seed=")?.parse().ok()
+        }
+    })
+}
+
+fn reseed(mut game_rng: ResMut<GameRng>, seed: Res<GameSeed>) {
+    *game_rng = GameRng::from_seed(seed.value);
+}
+
+fn draw_next_seed(mut seed: ResMut<GameSeed>) {
+    if !seed.chosen {
+        seed.value = random();
+    }
+}
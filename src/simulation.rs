@@ -0,0 +1,528 @@
+use bevy::utils::{HashMap, HashSet};
+use once_cell::sync::Lazy;
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    Rng, SeedableRng,
+};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    actors::SpawnPosition,
+    board::{deal_tiles, Board, PushSlot},
+    boards_repository::{
+        BOARD_0, BOARD_BIG, BOARD_CLASSIC, BOARD_CROSS, BOARD_HEX, BOARD_ONE_WAY, BOARD_SPECIAL,
+    },
+    bots::{bot_commands_from_args, BotMove, BotPosition, ExternalBot, BOT_TIMEOUT},
+    movement::BridgeLevel,
+    solver::{pushed_player, pushed_treasure, walks_from, SolverState},
+    tile::Orientation,
+    GridPosition, NUM_PLAYERS, TREASURES_TO_GET,
+};
+
+// Games still going after this many rounds are counted as draws
+const MAX_ROUNDS: u32 = 300;
+const DEFAULT_GAMES: u32 = 100;
+// The boards compared when none is asked for
+const DEFAULT_BOARDS: [&str; 2] = ["board_0", "classic"];
+
+fn named_board(name: &str) -> Option<&'static Lazy<Board>> {
+    match name {
+        "board_0" => Some(&BOARD_0),
+        "special" => Some(&BOARD_SPECIAL),
+        "classic" => Some(&BOARD_CLASSIC),
+        "one_way" => Some(&BOARD_ONE_WAY),
+        "cross" => Some(&BOARD_CROSS),
+        "hex" => Some(&BOARD_HEX),
+        "big" => Some(&BOARD_BIG),
+        _ => None,
+    }
+}
+
+// The values following a `--name` argument, or given as `--name=value`
+pub fn arg_values(args: &[String], name: &str) -> Vec<String> {
+    args.iter()
+        .enumerate()
+        .filter_map(|(i, arg)| {
+            if arg == name {
+                args.get(i + 1).cloned()
+            } else {
+                arg.strip_prefix(name)
+                    .and_then(|rest| rest.strip_prefix('='))
+                    .map(str::to_string)
+            }
+        })
+        .collect()
+}
+
+pub fn arg_value(args: &[String], name: &str) -> Option<String> {
+    arg_values(args, name).into_iter().next()
+}
+
+// What a seat chose to do on its turn
+struct BotTurn {
+    slot: usize,
+    orientation: Orientation,
+    destination: (GridPosition, BridgeLevel),
+    // whether some push let the seat reach its treasure
+    reachable: bool,
+    // the number of different pushes the seat could choose from
+    push_options: usize,
+}
+
+fn choose_turn(
+    board: &Board,
+    slots: &[PushSlot],
+    state: &SolverState,
+    rng: &mut impl Rng,
+) -> Option<BotTurn> {
+    // Reach the treasure if some push allows it, or else get as close to it as possible
+    // Ties are broken at random, so that seats don't all play the same way
+    let mut pushes_seen = HashSet::default();
+    let mut best_score = i32::MAX;
+    let mut best = vec![];
+    let mut reachable = false;
+    for slot in (0..slots.len()).filter(|&slot| Some(slot) != state.forbidden_slot) {
+        for turns in 0..board.topology.sides() as i32 {
+            let orientation = Orientation::new(turns);
+            let Some(pushed) = state.pushed(board, slots, slot, orientation) else {
+                continue;
+            };
+            if !pushes_seen.insert(pushed.key()) {
+                continue;
+            }
+            let walks = walks_from(pushed.player, &pushed.passages(board), board);
+            // in a set order, so that a seed always gives the same games
+            let mut destinations = walks.keys().copied().collect::<Vec<_>>();
+            destinations.sort_by_key(|(pos, level)| (pos.x_pos, pos.y_pos, *level as u8));
+            for destination in destinations {
+                reachable |= pushed.treasure == Some(destination.0);
+                let score = pushed
+                    .treasure
+                    .map_or(0, |pos| board.topology.distance(&destination.0, &pos));
+                if score < best_score {
+                    best_score = score;
+                    best.clear();
+                }
+                if score == best_score {
+                    best.push((slot, orientation, destination));
+                }
+            }
+        }
+    }
+    let &(slot, orientation, destination) = best.choose(rng)?;
+    Some(BotTurn {
+        slot,
+        orientation,
+        destination,
+        reachable,
+        push_options: pushes_seen.len(),
+    })
+}
+
+fn external_turn(
+    bot: &mut ExternalBot,
+    board: &Board,
+    slots: &[PushSlot],
+    state: &SolverState,
+    players: &[(GridPosition, BridgeLevel)],
+    seat: usize,
+    built_in: BotTurn,
+) -> BotTurn {
+    // The move of an external bot, or the one of the built-in bot when it can't be played
+    let position = BotPosition {
+        board,
+        slots,
+        state,
+        illegal: state.forbidden_slot.into_iter().collect(),
+        players: (0..).zip(players.iter().copied()).collect(),
+        you: seat as i32,
+        targets: vec![state.treasure],
+    };
+    let answer = bot
+        .send(&position.lines())
+        .ok()
+        .and_then(|()| bot.wait_answer(BOT_TIMEOUT));
+    let pushed = answer
+        .as_deref()
+        .and_then(BotMove::parse)
+        .filter(|chosen| Some(chosen.slot) != state.forbidden_slot)
+        .and_then(|chosen| {
+            let orientation = chosen.orientation(state.spare.1);
+            let pushed = state.pushed(board, slots, chosen.slot, orientation)?;
+            Some((chosen, orientation, pushed))
+        });
+    let Some((chosen, orientation, pushed)) = pushed else {
+        eprintln!(
+            "Unusable answer of the bot of seat {}: {answer:?}",
+            seat + 1
+        );
+        return built_in;
+    };
+    // Out of reach, the bot stays where the push left it
+    let walks = walks_from(pushed.player, &pushed.passages(board), board);
+    let destination = [BridgeLevel::Over, BridgeLevel::Under]
+        .into_iter()
+        .map(|level| (chosen.destination, level))
+        .find(|destination| walks.contains_key(destination))
+        .unwrap_or(pushed.player);
+    BotTurn {
+        slot: chosen.slot,
+        orientation,
+        destination,
+        ..built_in
+    }
+}
+
+// The starting cells of the players, then of the treasures, as the game picks them
+fn spawn_cells(
+    board: &Board,
+    num_players: usize,
+    num_treasures: usize,
+    rng: &mut impl Rng,
+) -> (Vec<GridPosition>, Vec<GridPosition>) {
+    let (max_x, max_y) = board.max_coords();
+    let cells = (0..=max_y)
+        .flat_map(|y_pos| (0..=max_x).map(move |x_pos| GridPosition { x_pos, y_pos }))
+        .filter(|pos| board.contains(pos))
+        .collect::<Vec<_>>();
+    let pick = |set: Option<&SpawnPosition>, used: &[GridPosition], rng: &mut ChaCha8Rng| match set
+    {
+        Some(SpawnPosition::Position(pos)) => *pos,
+        _ => cells
+            .iter()
+            .filter(|pos| !used.contains(pos))
+            .choose(rng)
+            .or(cells.first())
+            .copied()
+            .unwrap_or_default(),
+    };
+    let mut rng = ChaCha8Rng::seed_from_u64(rng.gen());
+    let mut spawns = vec![];
+    for id in 0..num_players {
+        let spawn = pick(board.spawn_positions.get(id), &spawns, &mut rng);
+        spawns.push(spawn);
+    }
+    let mut treasures = vec![];
+    for id in 0..num_treasures {
+        let used = [spawns.as_slice(), treasures.as_slice()].concat();
+        let treasure = pick(board.treasure_positions.get(id), &used, &mut rng);
+        treasures.push(treasure);
+    }
+    (spawns, treasures)
+}
+
+// How one game went
+struct GameRecord {
+    // None if no one finished in time
+    winner: Option<usize>,
+    rounds: u32,
+    turns: u32,
+    push_options: usize,
+    unreachable_turns: u32,
+}
+
+fn play_game(
+    layout: &Board,
+    num_players: usize,
+    treasures_to_get: usize,
+    bots: &mut HashMap<i32, ExternalBot>,
+    rng: &mut impl Rng,
+) -> GameRecord {
+    let board = deal_tiles(layout, rng);
+    let slots = board.push_slots();
+    let num_treasures = num_players * treasures_to_get;
+    let (spawns, treasure_cells) = spawn_cells(&board, num_players, num_treasures, rng);
+
+    let mut state = SolverState::from_board(&board, spawns[0], treasure_cells[0]);
+    let mut players = spawns
+        .iter()
+        .map(|&pos| (pos, BridgeLevel::Over))
+        .collect::<Vec<_>>();
+    let mut treasures = treasure_cells.into_iter().map(Some).collect::<Vec<_>>();
+    // each seat collects the last treasure of its deck first
+    let mut ids = (0..num_treasures).collect::<Vec<_>>();
+    ids.shuffle(rng);
+    let mut decks = ids
+        .chunks(treasures_to_get)
+        .map(<[usize]>::to_vec)
+        .collect::<Vec<_>>();
+
+    let mut record = GameRecord {
+        winner: None,
+        rounds: 0,
+        turns: 0,
+        push_options: 0,
+        unreachable_turns: 0,
+    };
+    let mut seat = 0;
+    while record.rounds < MAX_ROUNDS {
+        let Some(&target) = decks[seat].last() else {
+            break;
+        };
+        state.player = players[seat];
+        state.treasure = treasures[target];
+        let Some(mut turn) = choose_turn(&board, &slots, &state, rng) else {
+            break;
+        };
+        if let Some(bot) = bots.get_mut(&(seat as i32)) {
+            turn = external_turn(bot, &board, &slots, &state, &players, seat, turn);
+        }
+        record.turns += 1;
+        record.push_options += turn.push_options;
+        if !turn.reachable {
+            record.unreachable_turns += 1;
+        }
+
+        // Play the turn: the push moves everyone and every treasure on the line
+        let slot = &slots[turn.slot];
+        if let Some(pushed) = state.pushed(&board, &slots, turn.slot, turn.orientation) {
+            state = pushed;
+        }
+        for player in players.iter_mut() {
+            *player = pushed_player(slot, *player);
+        }
+        for treasure in treasures.iter_mut() {
+            *treasure = pushed_treasure(slot, *treasure);
+        }
+        players[seat] = turn.destination;
+        if treasures[target] == Some(turn.destination.0) {
+            decks[seat].pop();
+            if decks[seat].is_empty() {
+                record.winner = Some(seat);
+                record.rounds += 1;
+                break;
+            }
+        }
+
+        seat = (seat + 1) % num_players;
+        if seat == 0 {
+            record.rounds += 1;
+            state.end_round();
+        }
+    }
+    record
+}
+
+// The results of the games on one board
+struct BoardStats {
+    board: String,
+    games: u32,
+    wins: Vec<u32>,
+    draws: u32,
+    rounds: u32,
+    turns: u32,
+    push_options: usize,
+    unreachable_turns: u32,
+}
+
+impl BoardStats {
+    fn ratio(count: f64, total: f64) -> f64 {
+        if total > 0.0 {
+            count / total
+        } else {
+            0.0
+        }
+    }
+
+    fn win_rates(&self) -> Vec<f64> {
+        self.wins
+            .iter()
+            .map(|&wins| Self::ratio(wins as f64, self.games as f64))
+            .collect()
+    }
+
+    fn draw_rate(&self) -> f64 {
+        Self::ratio(self.draws as f64, self.games as f64)
+    }
+
+    // in rounds, over the games someone won
+    fn average_length(&self) -> f64 {
+        Self::ratio(self.rounds as f64, (self.games - self.draws) as f64)
+    }
+
+    // the different pushes a seat could choose from, not the one push it made
+    fn push_options_per_turn(&self) -> f64 {
+        Self::ratio(self.push_options as f64, self.turns as f64)
+    }
+
+    fn unreachable_rate(&self) -> f64 {
+        Self::ratio(self.unreachable_turns as f64, self.turns as f64)
+    }
+}
+
+fn simulate_board(
+    name: &str,
+    layout: &Board,
+    games: u32,
+    num_players: usize,
+    treasures_to_get: usize,
+    seed: u64,
+    bots: &mut HashMap<i32, ExternalBot>,
+) -> BoardStats {
+    let mut stats = BoardStats {
+        board: name.to_string(),
+        games,
+        wins: vec![0; num_players],
+        draws: 0,
+        rounds: 0,
+        turns: 0,
+        push_options: 0,
+        unreachable_turns: 0,
+    };
+    for game in 0..games {
+        // each game has its own seed, so that any of them can be played again
+        let mut rng = ChaCha8Rng::seed_from_u64(seed.wrapping_add(game as u64));
+        let record = play_game(layout, num_players, treasures_to_get, bots, &mut rng);
+        match record.winner {
+            Some(seat) => {
+                stats.wins[seat] += 1;
+                stats.rounds += record.rounds;
+            }
+            None => stats.draws += 1,
+        }
+        stats.turns += record.turns;
+        stats.push_options += record.push_options;
+        stats.unreachable_turns += record.unreachable_turns;
+    }
+    stats
+}
+
+fn to_json(all_stats: &[BoardStats], num_players: usize) -> String {
+    let boards = all_stats
+        .iter()
+        .map(|stats| {
+            let win_rates = stats
+                .win_rates()
+                .iter()
+                .map(|rate| format!("{rate:.4}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "    {{\n      \"board\": \"{}\",\n      \"games\": {},\n      \"players\": {num_players},\n      \"win_rate_by_seat\": [{win_rates}],\n      \"draw_rate\": {:.4},\n      \"average_game_length\": {:.2},\n      \"push_options_per_turn\": {:.2},\n      \"unreachable_target_rate\": {:.4}\n    }}",
+                stats.board,
+                stats.games,
+                stats.draw_rate(),
+                stats.average_length(),
+                stats.push_options_per_turn(),
+                stats.unreachable_rate()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",\n");
+    format!("{{\n  \"boards\": [\n{boards}\n  ]\n}}")
+}
+
+fn to_csv(all_stats: &[BoardStats], num_players: usize) -> String {
+    // One line per seat, the board figures repeated on each of them
+    let mut lines = vec!["board,games,players,seat,win_rate,draw_rate,average_game_length,push_options_per_turn,unreachable_target_rate".to_string()];
+    for stats in all_stats {
+        for (seat, rate) in stats.win_rates().iter().enumerate() {
+            lines.push(format!(
+                "{},{},{num_players},{},{rate:.4},{:.4},{:.2},{:.2},{:.4}",
+                stats.board,
+                stats.games,
+                seat + 1,
+                stats.draw_rate(),
+                stats.average_length(),
+                stats.push_options_per_turn(),
+                stats.unreachable_rate()
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+pub fn simulate_from_args() {
+    // Bots play `--games <number>` games against each other on each board, and the stats of
+    // their games are printed, as JSON or with `--format csv`
+    // `--board <name>` picks a board (both board_0 and classic by default),
+    // and `--players`, `--treasures` and `--seed` change the games
+    // Seats are played by external programs with `--bot <seat>=<command>`, as in the game
+    let args = std::env::args().collect::<Vec<_>>();
+    let games = arg_value(&args, "--games").unwrap_or(DEFAULT_GAMES.to_string());
+    let Ok(games) = games.parse::<u32>() else {
+        eprintln!("Not a number of games: {games}");
+        std::process::exit(1);
+    };
+    let number = |name: &str, default: usize| {
+        arg_value(&args, name)
+            .and_then(|value| value.parse::<usize>().ok())
+            .unwrap_or(default)
+            .max(1)
+    };
+    let num_players = number("--players", NUM_PLAYERS as usize);
+    let treasures_to_get = number("--treasures", TREASURES_TO_GET as usize);
+    let seed = arg_value(&args, "--seed")
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(0);
+    let names = arg_value(&args, "--board")
+        .map_or(DEFAULT_BOARDS.map(str::to_string).to_vec(), |name| {
+            vec![name]
+        });
+
+    let mut bots = HashMap::default();
+    for (id, command) in bot_commands_from_args() {
+        match ExternalBot::start(&command) {
+            Ok(bot) => {
+                bots.insert(id, bot);
+            }
+            Err(error) => {
+                eprintln!("Could not start the bot of seat {}: {error}", id + 1);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let mut all_stats = vec![];
+    for name in names {
+        let Some(layout) = named_board(&name) else {
+            eprintln!("Unknown board: {name}");
+            std::process::exit(1);
+        };
+        all_stats.push(simulate_board(
+            &name,
+            Lazy::force(layout),
+            games,
+            num_players,
+            treasures_to_get,
+            seed,
+            &mut bots,
+        ));
+    }
+    match arg_value(&args, "--format").as_deref() {
+        Some("csv") => println!("{}", to_csv(&all_stats, num_players)),
+        _ => println!("{}", to_json(&all_stats, num_players)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn reads_both_argument_forms() {
+        let args = args("simulate --games 20 --board=special --format csv");
+        assert_eq!(arg_value(&args, "--games").as_deref(), Some("20"));
+        assert_eq!(arg_value(&args, "--board").as_deref(), Some("special"));
+        assert_eq!(arg_value(&args, "--format").as_deref(), Some("csv"));
+        assert_eq!(arg_value(&args, "--seed"), None);
+    }
+
+    #[test]
+    fn reads_repeated_arguments_in_order() {
+        let args = args("simulate --bot ./first --games 5 --bot=./second");
+        assert_eq!(arg_values(&args, "--bot"), ["./first", "./second"]);
+        assert_eq!(arg_value(&args, "--bot").as_deref(), Some("./first"));
+    }
+
+    #[test]
+    fn ignores_missing_values_and_longer_names() {
+        // a name at the end has no value, and --gamesx is another argument
+        let args = args("simulate --gamesx=3 --games-count 4 --games");
+        assert!(arg_values(&args, "--games").is_empty());
+    }
+}
@@ -0,0 +1,528 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    board::{Board, PushSlot},
+    movement::{get_next_steps, BridgeLevel, TilePassages},
+    player::Player,
+    tile::{get_oriented_ways, Orientation, TileType},
+    tile_registry::TileBehaviour,
+    GridPosition,
+};
+
+// The tiles of the game being played, as the solver needs them
+pub type SolverTilesQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GridPosition,
+        &'static TileType,
+        &'static Orientation,
+    ),
+    Without<Player>,
+>;
+
+// How far a search may go before giving up
+#[derive(Debug, Clone, Copy)]
+pub struct SearchLimits {
+    pub max_turns: usize,
+    // the number of positions kept in memory, each one taking a few dozen bytes
+    pub max_states: usize,
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        SearchLimits {
+            max_turns: 4,
+            max_states: 200_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveFailure {
+    // No solution in max_turns turns
+    NoSolution,
+    // Too many positions to look through
+    TooManyStates,
+}
+
+// One turn of a solution: the spare tile is turned, pushed, then the player walks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolutionTurn {
+    // the orientation to give the spare tile before pushing it
+    pub orientation: Orientation,
+    // how many times to turn the spare tile clockwise to get there
+    pub clockwise_turns: u8,
+    // index in the push slots of the board
+    pub slot: usize,
+    // the tiles walked through after the push, without the one the player starts on
+    pub path: Vec<GridPosition>,
+}
+
+// What identifies a position, the orientation of the spare doesn't matter as it can be turned
+// at will
+// The whole position and not a hash of it, so that two positions are never taken for each other
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StateKey {
+    tiles: Box<[u32]>,
+    spare: TileType,
+    player: (GridPosition, BridgeLevel),
+    treasure: Option<GridPosition>,
+    forbidden_slot: Option<usize>,
+}
+
+// A position of a single player game, as far as the rules are concerned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolverState {
+    // the tiles of the board, in the order of the board array (row by row from the top)
+    tiles: Vec<(TileType, Orientation)>,
+    pub spare: (TileType, Orientation),
+    pub player: (GridPosition, BridgeLevel),
+    // None while the treasure is on the spare tile
+    pub treasure: Option<GridPosition>,
+    // the slot that would undo the last push
+    pub forbidden_slot: Option<usize>,
+}
+
+impl SolverState {
+    // The tiles are given with their positions, the ones outside of the board are ignored
+    pub fn new(
+        board: &Board,
+        tiles: impl IntoIterator<Item = (GridPosition, TileType, Orientation)>,
+        spare: (TileType, Orientation),
+        player: (GridPosition, BridgeLevel),
+        treasure: Option<GridPosition>,
+        forbidden_slot: Option<usize>,
+    ) -> Self {
+        let mut cells = vec![(TileType::Void, Orientation::default()); board.tiles.len()];
+        for (pos, tile_type, orientation) in tiles {
+            if let Some(index) = cell_index(board, &pos) {
+                cells[index] = (tile_type, orientation);
+            }
+        }
+        SolverState {
+            tiles: cells,
+            spare,
+            player,
+            treasure,
+            forbidden_slot,
+        }
+    }
+
+    // The start of a game on a board whose tiles are all set
+    pub fn from_board(board: &Board, start: GridPosition, target: GridPosition) -> Self {
+        let (max_x, max_y) = board.max_coords();
+        let tiles = (0..=max_y)
+            .flat_map(|y_pos| (0..=max_x).map(move |x_pos| GridPosition { x_pos, y_pos }))
+            .map(|pos| {
+                let tile = board.tiles[[(max_y - pos.y_pos) as usize, pos.x_pos as usize]];
+                (pos, tile.tile_type, tile.orientation)
+            });
+        let spare = board
+            .external_tiles
+            .first()
+            .map_or((TileType::Void, Orientation::default()), |tile| {
+                (tile.tile_type, tile.orientation)
+            });
+        SolverState::new(
+            board,
+            tiles,
+            spare,
+            (start, BridgeLevel::Over),
+            Some(target),
+            None,
+        )
+    }
+
+    pub fn key(&self) -> StateKey {
+        // One number per tile, from its kind and the way it is turned
+        let tile_code = |(tile_type, orientation): &(TileType, Orientation)| {
+            let kind = match tile_type {
+                TileType::Void => 0,
+                TileType::Any => 1,
+                TileType::Kind(index) => *index as u32 + 2,
+            };
+            kind * 12 + orientation.turns(tile_type.topology()) as u32
+        };
+        StateKey {
+            tiles: self.tiles.iter().map(tile_code).collect(),
+            spare: self.spare.0,
+            player: self.player,
+            treasure: self.treasure,
+            forbidden_slot: self.forbidden_slot,
+        }
+    }
+
+    pub fn passages(&self, board: &Board) -> HashMap<GridPosition, TilePassages> {
+        let (max_x, max_y) = board.max_coords();
+        (0..=max_y)
+            .flat_map(|y_pos| (0..=max_x).map(move |x_pos| GridPosition { x_pos, y_pos }))
+            .filter_map(|pos| {
+                let (tile_type, orientation) = self.tiles[cell_index(board, &pos)?];
+                let (open_ways, entry_ways, under_ways) = get_oriented_ways(tile_type, orientation);
+                Some((
+                    pos,
+                    TilePassages {
+                        tile_type,
+                        open_ways,
+                        entry_ways,
+                        under_ways,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    // Push the spare tile, turned to orientation, from a slot
+    pub fn pushed(
+        &self,
+        board: &Board,
+        slots: &[PushSlot],
+        slot_index: usize,
+        orientation: Orientation,
+    ) -> Option<Self> {
+        let slot = slots.get(slot_index)?;
+        let line = slot
+            .line
+            .iter()
+            .map(|pos| cell_index(board, pos))
+            .collect::<Option<Vec<_>>>()?;
+        let mut state = self.clone();
+
+        // the tiles move one step along the line, the last one becomes the spare
+        state.spare = self.tiles[*line.last()?];
+        for pair in line.windows(2) {
+            state.tiles[pair[1]] = self.tiles[pair[0]];
+        }
+        state.tiles[line[0]] = (self.spare.0, orientation);
+
+        state.player = pushed_player(slot, self.player);
+        state.treasure = pushed_treasure(slot, self.treasure);
+
+        state.forbidden_slot = slots
+            .iter()
+            .position(|other| other.pos == slot.exit && other.dir == slot.dir.opposite());
+        Some(state)
+    }
+
+    // The rotator tiles turn at the end of every round
+    pub fn end_round(&mut self) {
+        for (tile_type, orientation) in self.tiles.iter_mut().chain([&mut self.spare]) {
+            if tile_type.behaviour() == TileBehaviour::Rotator {
+                *orientation = orientation.rotated_anticlockwise();
+            }
+        }
+    }
+}
+
+// The tile after pos in the line of a slot, None for the last one or outside of the line
+fn next_in_line(slot: &PushSlot, pos: &GridPosition) -> Option<GridPosition> {
+    let index = slot.line.iter().position(|other| other == pos)?;
+    slot.line.get(index + 1).copied()
+}
+
+// Players pushed out come back at the other end of the line
+pub fn pushed_player(
+    slot: &PushSlot,
+    (pos, level): (GridPosition, BridgeLevel),
+) -> (GridPosition, BridgeLevel) {
+    if slot.line.last() == Some(&pos) {
+        (slot.line[0], BridgeLevel::Over)
+    } else {
+        (next_in_line(slot, &pos).unwrap_or(pos), level)
+    }
+}
+
+// A treasure stays on its tile, None while on the spare tile
+pub fn pushed_treasure(slot: &PushSlot, treasure: Option<GridPosition>) -> Option<GridPosition> {
+    match treasure {
+        None => Some(slot.line[0]),
+        Some(pos) if slot.line.last() == Some(&pos) => None,
+        Some(pos) => Some(next_in_line(slot, &pos).unwrap_or(pos)),
+    }
+}
+
+fn cell_index(board: &Board, pos: &GridPosition) -> Option<usize> {
+    // the board array is stored top-down
+    if !board.contains(pos) {
+        return None;
+    }
+    let (max_x, max_y) = board.max_coords();
+    Some(((max_y - pos.y_pos) * (max_x + 1) + pos.x_pos) as usize)
+}
+
+pub type Walks = HashMap<(GridPosition, BridgeLevel), Option<(GridPosition, BridgeLevel)>>;
+
+pub fn walks_from(
+    start: (GridPosition, BridgeLevel),
+    passages: &HashMap<GridPosition, TilePassages>,
+    board: &Board,
+) -> Walks {
+    // Every place the player can walk to, with the step it is reached from
+    let mut previous = Walks::default();
+    previous.insert(start, None);
+    let mut to_visit = vec![start];
+    while let Some((pos, level)) = to_visit.pop() {
+        for step in get_next_steps(pos, level, passages, board) {
+            if !previous.contains_key(&step) {
+                previous.insert(step, Some((pos, level)));
+                to_visit.push(step);
+            }
+        }
+    }
+    previous
+}
+
+fn path_to(walks: &Walks, end: (GridPosition, BridgeLevel)) -> Vec<GridPosition> {
+    let mut path = vec![];
+    let mut current = end;
+    while let Some(Some(previous)) = walks.get(&current) {
+        path.push(current.0);
+        current = *previous;
+    }
+    path.reverse();
+    path
+}
+
+// A turn of the search, replayed from the start to describe the solution
+#[derive(Debug, Clone, Copy)]
+struct SearchMove {
+    parent: usize,
+    slot: usize,
+    orientation: Orientation,
+    destination: (GridPosition, BridgeLevel),
+}
+
+pub fn solve(
+    board: &Board,
+    start: &SolverState,
+    limits: SearchLimits,
+) -> Result<Vec<SolutionTurn>, SolveFailure> {
+    // Breadth first search over the turns, so the first solution found is one of the shortest
+    // Only the moves leading to each position are kept, and the keys of the positions seen
+    let slots = board.push_slots();
+    let sides = board.topology.sides() as i32;
+    let mut moves: Vec<Option<SearchMove>> = vec![None];
+    let mut seen = HashSet::default();
+    seen.insert(start.key());
+    let mut layer = vec![(0, start.clone())];
+
+    for turn in 1..=limits.max_turns {
+        let mut next_layer = vec![];
+        for (node, state) in &layer {
+            let mut pushes_seen = HashSet::default();
+            for slot in (0..slots.len()).filter(|&slot| Some(slot) != state.forbidden_slot) {
+                for turns in 0..sides {
+                    let orientation = Orientation::new(turns);
+                    let Some(pushed) = state.pushed(board, &slots, slot, orientation) else {
+                        continue;
+                    };
+                    // turning a symmetric tile can give the same board
+                    if !pushes_seen.insert(pushed.key()) {
+                        continue;
+                    }
+                    let walks = walks_from(pushed.player, &pushed.passages(board), board);
+                    // in a set order, so that the same solution is found every time
+                    let mut destinations = walks.keys().copied().collect::<Vec<_>>();
+                    destinations.sort_by_key(|(pos, level)| (pos.x_pos, pos.y_pos, *level as u8));
+
+                    let found = destinations
+                        .iter()
+                        .find(|(pos, _)| Some(*pos) == pushed.treasure)
+                        .copied();
+                    if let Some(destination) = found {
+                        moves.push(Some(SearchMove {
+                            parent: *node,
+                            slot,
+                            orientation,
+                            destination,
+                        }));
+                        return Ok(replay(board, &slots, start, &moves, moves.len() - 1));
+                    }
+                    if turn == limits.max_turns {
+                        continue;
+                    }
+
+                    for destination in destinations {
+                        let mut next = pushed.clone();
+                        next.player = destination;
+                        next.end_round();
+                        if seen.insert(next.key()) {
+                            moves.push(Some(SearchMove {
+                                parent: *node,
+                                slot,
+                                orientation,
+                                destination,
+                            }));
+                            next_layer.push((moves.len() - 1, next));
+                            if moves.len() > limits.max_states {
+                                return Err(SolveFailure::TooManyStates);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if next_layer.is_empty() {
+            break;
+        }
+        layer = next_layer;
+    }
+    Err(SolveFailure::NoSolution)
+}
+
+fn replay(
+    board: &Board,
+    slots: &[PushSlot],
+    start: &SolverState,
+    moves: &[Option<SearchMove>],
+    last: usize,
+) -> Vec<SolutionTurn> {
+    // The moves from the start to the last one
+    let mut chain = vec![];
+    let mut node = last;
+    while let Some(search_move) = moves[node] {
+        chain.push(search_move);
+        node = search_move.parent;
+    }
+    chain.reverse();
+
+    // Play them again to find the paths, and how to turn the spare tile
+    let sides = board.topology.sides();
+    let mut state = start.clone();
+    let mut solution = vec![];
+    for search_move in chain {
+        let current = state.spare.1.turns(board.topology);
+        let wanted = search_move.orientation.turns(board.topology);
+        let Some(pushed) = state.pushed(board, slots, search_move.slot, search_move.orientation)
+        else {
+            break;
+        };
+        let walks = walks_from(pushed.player, &pushed.passages(board), board);
+        solution.push(SolutionTurn {
+            orientation: search_move.orientation,
+            clockwise_turns: (current + sides - wanted) % sides,
+            slot: search_move.slot,
+            path: path_to(&walks, search_move.destination),
+        });
+        state = pushed;
+        state.player = search_move.destination;
+        state.end_round();
+    }
+    solution
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+    use crate::{board::TileInfo, movement::CanMove, topology::Topology};
+
+    fn tile(name: &str, turns: i32, can_move: CanMove) -> TileInfo {
+        TileInfo {
+            tile_type: TileType::named(name),
+            orientation: Orientation::new(turns),
+            can_move,
+        }
+    }
+
+    // The bottom left corner is two turns away from the top right one
+    fn tiny_board() -> Board {
+        Board {
+            tiles: array![
+                [
+                    tile("corner", 0, CanMove::No),
+                    tile("straight", 3, CanMove::Yes),
+                    tile("t_shape", 0, CanMove::No),
+                ],
+                [
+                    tile("straight", 0, CanMove::Yes),
+                    tile("t_shape", 0, CanMove::Yes),
+                    tile("corner", 1, CanMove::Yes),
+                ],
+                [
+                    tile("t_shape", 3, CanMove::No),
+                    tile("straight", 1, CanMove::Yes),
+                    tile("corner", 0, CanMove::No),
+                ],
+            ],
+            spawn_positions: vec![],
+            treasure_positions: vec![],
+            external_tiles: vec![tile("straight", 0, CanMove::Yes)],
+            tile_deck: vec![],
+            topology: Topology::Square,
+        }
+    }
+
+    const START: GridPosition = GridPosition { x_pos: 0, y_pos: 0 };
+    const TARGET: GridPosition = GridPosition { x_pos: 2, y_pos: 2 };
+
+    #[test]
+    fn finds_a_shortest_solution() {
+        let board = tiny_board();
+        let state = SolverState::from_board(&board, START, TARGET);
+        let solution = solve(&board, &state, SearchLimits::default()).unwrap();
+        assert_eq!(solution.len(), 2);
+
+        // no push of a single turn gets the player there, whatever the spare tile orientation
+        let slots = board.push_slots();
+        for slot in 0..slots.len() {
+            for turns in 0..board.topology.sides() as i32 {
+                let Some(pushed) = state.pushed(&board, &slots, slot, Orientation::new(turns))
+                else {
+                    continue;
+                };
+                let walks = walks_from(pushed.player, &pushed.passages(&board), &board);
+                assert!(walks.keys().all(|(pos, _)| Some(*pos) != pushed.treasure));
+            }
+        }
+        let one_turn = SearchLimits {
+            max_turns: 1,
+            ..default()
+        };
+        assert_eq!(
+            solve(&board, &state, one_turn),
+            Err(SolveFailure::NoSolution)
+        );
+    }
+
+    #[test]
+    fn solution_walks_to_the_treasure() {
+        let board = tiny_board();
+        let slots = board.push_slots();
+        let mut state = SolverState::from_board(&board, START, TARGET);
+        let solution = solve(&board, &state, SearchLimits::default()).unwrap();
+
+        for turn in &solution {
+            let pushed = state
+                .pushed(&board, &slots, turn.slot, turn.orientation)
+                .unwrap();
+            let walks = walks_from(pushed.player, &pushed.passages(&board), &board);
+            let end = turn.path.last().copied().unwrap_or(pushed.player.0);
+            let (_, level) = walks.keys().find(|(pos, _)| *pos == end).unwrap();
+            state = pushed;
+            state.player = (end, *level);
+            state.end_round();
+        }
+        // the pushes may have moved the treasure along
+        assert_eq!(Some(state.player.0), state.treasure);
+    }
+
+    #[test]
+    fn gives_up_when_there_are_too_many_positions() {
+        let board = tiny_board();
+        let state = SolverState::from_board(&board, START, TARGET);
+        let limits = SearchLimits {
+            max_turns: 4,
+            max_states: 1,
+        };
+        assert_eq!(
+            solve(&board, &state, limits),
+            Err(SolveFailure::TooManyStates)
+        );
+    }
+}
@@ -0,0 +1,408 @@
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::{
+    audio::{AddAudioSource, Source, Volume},
+    prelude::*,
+};
+
+use crate::phases::GameState;
+
+const SAMPLE_RATE: u32 = 44_100;
+// Fade in and out of every note, in seconds, to avoid clicks
+const ATTACK: f32 = 0.005;
+const RELEASE: f32 = 0.03;
+
+// A sound effect to be played, sent by the game systems
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum SoundEffect {
+    TileSlide,
+    TileRotate,
+    IllegalPush,
+    Footstep,
+    TreasurePickup,
+    TurnChange,
+    GameEnd,
+    MinotaurCatch,
+}
+
+#[derive(Resource, Debug)]
+pub struct AudioSettings {
+    // volumes between 0 and 1
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            music_volume: 0.4,
+            effects_volume: 0.8,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    fn effective_music_volume(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.music_volume
+        }
+    }
+
+    fn effective_effects_volume(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.effects_volume
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Waveform {
+    Sine,
+    Square,
+    Triangle,
+    // Sample-and-hold noise, the note frequency sets how rough it sounds
+    Noise,
+}
+
+// A note of a tune, a frequency of 0 is a rest
+#[derive(Debug, Clone, Copy)]
+struct Note {
+    frequency: f32,
+    duration: f32,
+}
+
+// A synthesized sound, so that the game needs no audio files
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct Tune {
+    notes: Vec<Note>,
+    waveform: Waveform,
+    amplitude: f32,
+}
+
+impl Tune {
+    fn new(waveform: Waveform, amplitude: f32, notes: &[(f32, f32)]) -> Self {
+        Tune {
+            notes: notes
+                .iter()
+                .map(|&(frequency, duration)| Note {
+                    frequency,
+                    duration,
+                })
+                .collect(),
+            waveform,
+            amplitude,
+        }
+    }
+}
+
+pub struct TuneDecoder {
+    tune: Tune,
+    note_index: usize,
+    sample_in_note: u32,
+    phase: f32,
+    noise_state: u32,
+    held_noise: f32,
+}
+
+impl TuneDecoder {
+    fn next_noise(&mut self) -> f32 {
+        // xorshift, good enough for sound
+        self.noise_state ^= self.noise_state << 13;
+        self.noise_state ^= self.noise_state >> 17;
+        self.noise_state ^= self.noise_state << 5;
+        (self.noise_state as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+}
+
+impl Iterator for TuneDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        loop {
+            let note = *self.tune.notes.get(self.note_index)?;
+            let note_len = (note.duration * SAMPLE_RATE as f32) as u32;
+            if self.sample_in_note >= note_len {
+                // go to the next note
+                self.note_index += 1;
+                self.sample_in_note = 0;
+                self.phase = 0.0;
+                continue;
+            }
+
+            let time = self.sample_in_note as f32 / SAMPLE_RATE as f32;
+            self.sample_in_note += 1;
+            if note.frequency <= 0.0 {
+                // rest
+                return Some(0.0);
+            }
+
+            let previous_phase = self.phase;
+            self.phase = (self.phase + note.frequency / SAMPLE_RATE as f32).fract();
+            let value = match self.tune.waveform {
+                Waveform::Sine => (TAU * self.phase).sin(),
+                Waveform::Square => {
+                    if self.phase < 0.5 {
+                        1.0
+                    } else {
+                        -1.0
+                    }
+                }
+                Waveform::Triangle => 4.0 * (self.phase - 0.5).abs() - 1.0,
+                Waveform::Noise => {
+                    if self.phase < previous_phase {
+                        self.held_noise = self.next_noise();
+                    }
+                    self.held_noise
+                }
+            };
+            let envelope = (time / ATTACK).min(1.0) * ((note.duration - time) / RELEASE).min(1.0);
+            return Some(value * envelope * self.tune.amplitude);
+        }
+    }
+}
+
+impl Source for TuneDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(
+            self.tune.notes.iter().map(|note| note.duration).sum(),
+        ))
+    }
+}
+
+impl Decodable for Tune {
+    type DecoderItem = f32;
+    type Decoder = TuneDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        TuneDecoder {
+            tune: self.clone(),
+            note_index: 0,
+            sample_in_note: 0,
+            phase: 0.0,
+            noise_state: 0x9E37_79B9,
+            held_noise: 0.0,
+        }
+    }
+}
+
+// Handles to all of the game's sounds
+#[derive(Resource, Debug)]
+struct SoundLibrary {
+    tile_slide: Handle<Tune>,
+    tile_rotate: Handle<Tune>,
+    illegal_push: Handle<Tune>,
+    footstep: Handle<Tune>,
+    treasure_pickup: Handle<Tune>,
+    turn_change: Handle<Tune>,
+    game_end: Handle<Tune>,
+    minotaur_catch: Handle<Tune>,
+    menu_music: Handle<Tune>,
+    game_music: Handle<Tune>,
+}
+
+impl SoundLibrary {
+    fn effect(&self, effect: SoundEffect) -> Handle<Tune> {
+        match effect {
+            SoundEffect::TileSlide => self.tile_slide.clone(),
+            SoundEffect::TileRotate => self.tile_rotate.clone(),
+            SoundEffect::IllegalPush => self.illegal_push.clone(),
+            SoundEffect::Footstep => self.footstep.clone(),
+            SoundEffect::TreasurePickup => self.treasure_pickup.clone(),
+            SoundEffect::TurnChange => self.turn_change.clone(),
+            SoundEffect::GameEnd => self.game_end.clone(),
+            SoundEffect::MinotaurCatch => self.minotaur_catch.clone(),
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+struct MusicTrack;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<Tune>()
+            .add_event::<SoundEffect>()
+            .insert_resource(AudioSettings::default())
+            .add_systems(PreStartup, create_sounds)
+            .add_systems(OnEnter(GameState::MainMenu), start_menu_music)
+            .add_systems(OnExit(GameState::MainMenu), stop_music)
+            .add_systems(OnEnter(GameState::Playing), start_game_music)
+            .add_systems(OnExit(GameState::Playing), stop_music)
+            .add_systems(
+                Update,
+                (play_sound_effects, toggle_mute, apply_music_volume),
+            );
+    }
+}
+
+fn create_sounds(mut commands: Commands, mut tunes: ResMut<Assets<Tune>>) {
+    // Note frequencies, in Hz
+    const C4: f32 = 261.63;
+    const E4: f32 = 329.63;
+    const G4: f32 = 392.0;
+    const A4: f32 = 440.0;
+    const C5: f32 = 523.25;
+    const D5: f32 = 587.33;
+    const E5: f32 = 659.25;
+    const G5: f32 = 783.99;
+    const C6: f32 = 1046.5;
+
+    commands.insert_resource(SoundLibrary {
+        tile_slide: tunes.add(Tune::new(
+            Waveform::Noise,
+            0.4,
+            &[(900.0, 0.08), (1400.0, 0.08), (2000.0, 0.12)],
+        )),
+        tile_rotate: tunes.add(Tune::new(Waveform::Square, 0.15, &[(E5, 0.05)])),
+        illegal_push: tunes.add(Tune::new(
+            Waveform::Square,
+            0.2,
+            &[(110.0, 0.12), (0.0, 0.04), (98.0, 0.18)],
+        )),
+        footstep: tunes.add(Tune::new(Waveform::Noise, 0.3, &[(400.0, 0.06)])),
+        treasure_pickup: tunes.add(Tune::new(
+            Waveform::Triangle,
+            0.4,
+            &[(C5, 0.07), (E5, 0.07), (G5, 0.07), (C6, 0.2)],
+        )),
+        turn_change: tunes.add(Tune::new(Waveform::Sine, 0.35, &[(G4, 0.1), (C5, 0.2)])),
+        game_end: tunes.add(Tune::new(
+            Waveform::Triangle,
+            0.4,
+            &[
+                (C5, 0.15),
+                (C5, 0.15),
+                (C5, 0.15),
+                (E5, 0.3),
+                (D5, 0.15),
+                (E5, 0.15),
+                (G5, 0.6),
+            ],
+        )),
+        minotaur_catch: tunes.add(Tune::new(
+            Waveform::Square,
+            0.3,
+            &[(82.0, 0.15), (0.0, 0.03), (73.0, 0.35)],
+        )),
+        menu_music: tunes.add(Tune::new(
+            Waveform::Sine,
+            0.25,
+            &[
+                (C4, 0.5),
+                (E4, 0.5),
+                (G4, 0.5),
+                (E4, 0.5),
+                (A4, 0.5),
+                (E4, 0.5),
+                (G4, 1.0),
+            ],
+        )),
+        game_music: tunes.add(Tune::new(
+            Waveform::Triangle,
+            0.2,
+            &[
+                (C4, 0.25),
+                (G4, 0.25),
+                (E4, 0.25),
+                (G4, 0.25),
+                (A4, 0.25),
+                (G4, 0.25),
+                (E4, 0.5),
+                (C4, 0.25),
+                (E4, 0.25),
+                (G4, 0.25),
+                (C5, 0.25),
+                (G4, 0.5),
+                (0.0, 0.5),
+            ],
+        )),
+    });
+}
+
+fn play_sound_effects(
+    mut commands: Commands,
+    mut effects: EventReader<SoundEffect>,
+    library: Res<SoundLibrary>,
+    audio_settings: Res<AudioSettings>,
+) {
+    let volume = audio_settings.effective_effects_volume();
+    for effect in effects.read() {
+        if volume > 0.0 {
+            commands.spawn(AudioSourceBundle {
+                source: library.effect(*effect),
+                settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_relative(volume)),
+            });
+        }
+    }
+}
+
+fn start_menu_music(
+    mut commands: Commands,
+    library: Res<SoundLibrary>,
+    audio_settings: Res<AudioSettings>,
+) {
+    spawn_music(&mut commands, library.menu_music.clone(), &audio_settings);
+}
+
+fn start_game_music(
+    mut commands: Commands,
+    library: Res<SoundLibrary>,
+    audio_settings: Res<AudioSettings>,
+) {
+    spawn_music(&mut commands, library.game_music.clone(), &audio_settings);
+}
+
+fn spawn_music(commands: &mut Commands, music: Handle<Tune>, audio_settings: &AudioSettings) {
+    commands.spawn((
+        AudioSourceBundle {
+            source: music,
+            settings: PlaybackSettings::LOOP.with_volume(Volume::new_relative(
+                audio_settings.effective_music_volume(),
+            )),
+        },
+        MusicTrack,
+    ));
+}
+
+fn stop_music(mut commands: Commands, music_query: Query<Entity, With<MusicTrack>>) {
+    for entity in &music_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn toggle_mute(mut audio_settings: ResMut<AudioSettings>, keys: Res<Input<KeyCode>>) {
+    if keys.just_pressed(KeyCode::M) {
+        audio_settings.muted = !audio_settings.muted;
+    }
+}
+
+fn apply_music_volume(
+    music_query: Query<&AudioSink, With<MusicTrack>>,
+    audio_settings: Res<AudioSettings>,
+) {
+    if audio_settings.is_changed() {
+        for sink in &music_query {
+            sink.set_volume(audio_settings.effective_music_volume());
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+
+use crate::{
+    phases::GameState,
+    player::Player,
+    ui::{ChosenLanguage, Language},
+    GameSettings,
+};
+
+// Tints of the tokens of each team
+const TEAM_COLORS: [Color; 2] = [Color::rgb(0.6, 0.8, 1.0), Color::rgb(1.0, 0.65, 0.55)];
+
+// How the players are grouped, teammates share one treasure deck
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Teams {
+    // Everyone plays for themselves
+    #[default]
+    Solo,
+    // The first player and their partner against the others
+    Pairs {
+        partner: i32,
+    },
+    // Everyone together against the minotaur
+    Coop,
+}
+
+impl Teams {
+    // The key of the treasure deck of a player
+    pub fn team_of(self, player_id: i32) -> i32 {
+        match self {
+            Teams::Solo => player_id,
+            Teams::Pairs { partner } => i32::from(player_id != 0 && player_id != partner),
+            Teams::Coop => 0,
+        }
+    }
+
+    pub fn members(self, team: i32, num_players: i32) -> Vec<i32> {
+        (0..num_players)
+            .filter(|&id| self.team_of(id) == team)
+            .collect()
+    }
+
+    pub fn team_ids(self, num_players: i32) -> Vec<i32> {
+        let mut teams = (0..num_players)
+            .map(|id| self.team_of(id))
+            .collect::<Vec<_>>();
+        teams.sort();
+        teams.dedup();
+        teams
+    }
+
+    // The next assignment offered in the menu
+    // Teammates not playing one after the other come first, the co-op mode last
+    pub fn next(self, num_players: i32) -> Self {
+        const PARTNERS: [i32; 3] = [2, 1, 3];
+        let position = match self {
+            Teams::Solo => 0,
+            Teams::Pairs { partner } => PARTNERS
+                .iter()
+                .position(|&other| other == partner)
+                .map_or(PARTNERS.len(), |index| index + 1),
+            Teams::Coop => return Teams::Solo,
+        };
+        PARTNERS
+            .get(position)
+            .filter(|&&partner| partner < num_players)
+            .map_or(Teams::Coop, |&partner| Teams::Pairs { partner })
+    }
+
+    pub fn color(self, player_id: i32) -> Color {
+        match self {
+            Teams::Solo | Teams::Coop => Color::WHITE,
+            Teams::Pairs { .. } => {
+                TEAM_COLORS[self.team_of(player_id) as usize % TEAM_COLORS.len()]
+            }
+        }
+    }
+
+    pub fn label(self, language: &Language, num_players: i32) -> String {
+        let names = |prefix: &str, team: i32| {
+            self.members(team, num_players)
+                .iter()
+                .map(|id| format!("{prefix}{}", id + 1))
+                .collect::<Vec<_>>()
+                .join("+")
+        };
+        match (language, self) {
+            (Language::French, Teams::Solo) => "Non".to_string(),
+            (Language::English, Teams::Solo) => "Off".to_string(),
+            (Language::French, Teams::Pairs { .. }) => {
+                format!("{} contre {}", names("J", 0), names("J", 1))
+            }
+            (Language::English, Teams::Pairs { .. }) => {
+                format!("{} vs {}", names("P", 0), names("P", 1))
+            }
+            (Language::French, Teams::Coop) => "Tous contre le minotaure".to_string(),
+            (Language::English, Teams::Coop) => "All vs the minotaur".to_string(),
+        }
+    }
+}
+
+pub struct TeamsPlugin;
+
+impl Plugin for TeamsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            tint_team_tokens.run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn tint_team_tokens(
+    mut player_query: Query<(&Player, &mut Sprite), Added<Player>>,
+    game_settings: Res<GameSettings>,
+) {
+    // Teammates have tokens of the same color
+    for (player, mut sprite) in &mut player_query {
+        sprite.color = game_settings.teams.color(player.id);
+    }
+}
+
+pub fn team_name(language: &ChosenLanguage, team: i32) -> String {
+    match language.lang {
+        Language::French => format!("Equipe {}", team + 1),
+        Language::English => format!("Team {}", team + 1),
+    }
+}
@@ -0,0 +1,365 @@
+use std::{collections::HashMap, fmt};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadState},
+    prelude::*,
+    reflect::TypePath,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{
+    board::Ground, tile::TileType, tile_registry::TILE_REGISTRY, topology::Topology,
+    ui::LocalizedName,
+};
+
+// All of the visual themes, in the assets folder
+// Read through the asset server like the treasure catalogue, the first one is used at start
+const THEMES_PATH: &str = "themes/base.themes.ron";
+
+#[derive(Asset, TypePath, Debug, Deserialize)]
+#[serde(transparent)]
+pub struct ThemeList(Vec<Theme>);
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ThemeError::Io(error) => write!(f, "could not read the themes: {error}"),
+            ThemeError::Ron(error) => write!(f, "invalid themes: {error}"),
+            ThemeError::Invalid(error) => write!(f, "invalid theme: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+impl From<std::io::Error> for ThemeError {
+    fn from(error: std::io::Error) -> Self {
+        ThemeError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for ThemeError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        ThemeError::Ron(error)
+    }
+}
+
+#[derive(Default)]
+struct ThemeListLoader;
+
+impl AssetLoader for ThemeListLoader {
+    type Asset = ThemeList;
+    type Settings = ();
+    type Error = ThemeError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<ThemeList, ThemeError>> {
+        Box::pin(async move {
+            let mut bytes = vec![];
+            reader.read_to_end(&mut bytes).await?;
+            let themes: ThemeList = ron::de::from_bytes(&bytes)?;
+            if themes.0.is_empty() {
+                return Err(ThemeError::Invalid("no theme is declared".to_string()));
+            }
+            for theme in &themes.0 {
+                if let Some(name) = theme
+                    .tiles
+                    .keys()
+                    .find(|name| TILE_REGISTRY.index_of(name).is_none())
+                {
+                    return Err(ThemeError::Invalid(format!(
+                        "{} has an unknown tile kind {name}",
+                        theme.id
+                    )));
+                }
+            }
+            Ok(themes)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["themes.ron"]
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Theme {
+    pub id: String,
+    pub name: LocalizedName,
+    // in the assets folder, the textures of the theme are relative to it
+    #[serde(default)]
+    folder: String,
+    // texture of each tile kind, by name
+    #[serde(default)]
+    tiles: HashMap<String, String>,
+    #[serde(default)]
+    ground: Option<String>,
+    #[serde(default)]
+    tile_back: Option<String>,
+    #[serde(default)]
+    hex_tile_back: Option<String>,
+    #[serde(default)]
+    token_frame: Option<String>,
+    pub colors: Palette,
+}
+
+impl Theme {
+    fn path(&self, file: &str) -> String {
+        if self.folder.is_empty() {
+            file.to_string()
+        } else {
+            format!("{}/{file}", self.folder)
+        }
+    }
+
+    // The kinds left out of the theme keep the texture of the registry
+    pub fn tile_texture(&self, tile_type: TileType) -> String {
+        match tile_type.kind() {
+            Some(kind) => match self.tiles.get(&kind.name) {
+                Some(file) => self.path(file),
+                None => kind.texture.clone(),
+            },
+            None => "corner.png".to_string(), //Should never be reached, default to corner
+        }
+    }
+
+    pub fn ground(&self) -> String {
+        match &self.ground {
+            Some(file) => self.path(file),
+            None => "ground.png".to_string(),
+        }
+    }
+
+    pub fn tile_back(&self, topology: Topology) -> String {
+        match (topology, &self.tile_back, &self.hex_tile_back) {
+            (Topology::Square, Some(file), _) | (Topology::Hex, _, Some(file)) => self.path(file),
+            (Topology::Square, None, _) => "tile_back.png".to_string(),
+            (Topology::Hex, _, None) => "hex_tile_back.png".to_string(),
+        }
+    }
+
+    // Tinted with the ring color of each seat
+    pub fn token_frame(&self) -> String {
+        match &self.token_frame {
+            Some(file) => self.path(file),
+            None => "players/ring.png".to_string(),
+        }
+    }
+}
+
+// The colors of the background and of the interface
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(from = "PaletteFile")]
+pub struct Palette {
+    pub background: Color,
+    pub panel: Color,
+    // the screen hiding the board between turns
+    pub overlay: Color,
+    pub text: Color,
+    pub button: Color,
+    pub highlight: Color,
+    // the power cards in the hand, and the one about to be played
+    pub card: Color,
+    pub armed_card: Color,
+    // tint of the tiles the current player can walk to
+    pub reachable: Color,
+    // tints of the push slot arrows, and of the slots blocked by a power card
+    pub slot: Color,
+    pub current_slot: Color,
+    pub illegal_slot: Color,
+    pub blocked_slot: Color,
+}
+
+// (red, green, blue, alpha)
+type Rgba = (f32, f32, f32, f32);
+
+#[derive(Deserialize)]
+struct PaletteFile {
+    background: Rgba,
+    panel: Rgba,
+    overlay: Rgba,
+    text: Rgba,
+    button: Rgba,
+    highlight: Rgba,
+    card: Rgba,
+    armed_card: Rgba,
+    reachable: Rgba,
+    slot: Rgba,
+    current_slot: Rgba,
+    illegal_slot: Rgba,
+    blocked_slot: Rgba,
+}
+
+impl From<PaletteFile> for Palette {
+    fn from(file: PaletteFile) -> Self {
+        let color = |(r, g, b, a): Rgba| Color::rgba(r, g, b, a);
+        Palette {
+            background: color(file.background),
+            panel: color(file.panel),
+            overlay: color(file.overlay),
+            text: color(file.text),
+            button: color(file.button),
+            highlight: color(file.highlight),
+            card: color(file.card),
+            armed_card: color(file.armed_card),
+            reachable: color(file.reachable),
+            slot: color(file.slot),
+            current_slot: color(file.current_slot),
+            illegal_slot: color(file.illegal_slot),
+            blocked_slot: color(file.blocked_slot),
+        }
+    }
+}
+
+// A color of the interface that follows the theme, when it changes during a game
+#[derive(Component, Debug, Clone, Copy)]
+pub enum ThemeColor {
+    Panel,
+    Overlay,
+    Text,
+    Button,
+}
+
+impl ThemeColor {
+    fn of(self, colors: &Palette) -> Color {
+        match self {
+            ThemeColor::Panel => colors.panel,
+            ThemeColor::Overlay => colors.overlay,
+            ThemeColor::Text => colors.text,
+            ThemeColor::Button => colors.button,
+        }
+    }
+}
+
+// Empty until the themes are loaded, the game waits for them before showing the menu
+#[derive(Resource, Debug, Default)]
+pub struct ChosenTheme {
+    index: usize,
+    themes: Vec<Theme>,
+}
+
+impl ChosenTheme {
+    // Back to the first theme, the classic one, if the chosen one is gone after a reload
+    pub fn get(&self) -> &Theme {
+        self.themes
+            .get(self.index)
+            .or(self.themes.first())
+            .expect("the themes are not loaded yet")
+    }
+
+    pub fn next(&mut self) {
+        self.index = (self.index + 1) % self.themes.len();
+    }
+}
+
+#[derive(Resource, Debug)]
+struct ThemeListHandle(Handle<ThemeList>);
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ChosenTheme::default())
+            .init_asset::<ThemeList>()
+            .init_asset_loader::<ThemeListLoader>()
+            .add_systems(Startup, load_themes)
+            .add_systems(
+                Update,
+                (
+                    update_themes,
+                    (apply_theme, dress_tiles, recolor_interface).run_if(themes_are_loaded),
+                )
+                    .chain(),
+            );
+    }
+}
+
+pub fn themes_are_loaded(theme: Res<ChosenTheme>) -> bool {
+    !theme.themes.is_empty()
+}
+
+fn load_themes(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ThemeListHandle(asset_server.load(THEMES_PATH)));
+}
+
+fn update_themes(
+    mut events: EventReader<AssetEvent<ThemeList>>,
+    theme_lists: Res<Assets<ThemeList>>,
+    handle: Res<ThemeListHandle>,
+    mut theme: ResMut<ChosenTheme>,
+    asset_server: Res<AssetServer>,
+) {
+    // The game can't be shown without them
+    if asset_server.load_state(&handle.0) == LoadState::Failed {
+        panic!("Could not load the themes from {THEMES_PATH}");
+    }
+    // Once loaded, and again whenever the file changes
+    for event in events.read() {
+        if event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0) {
+            if let Some(themes) = theme_lists.get(&handle.0) {
+                theme.themes = themes.0.clone();
+            }
+        }
+    }
+}
+
+fn apply_theme(
+    theme: Res<ChosenTheme>,
+    mut clear_color: ResMut<ClearColor>,
+    mut ground_query: Query<&mut Handle<Image>, With<Ground>>,
+    asset_server: Res<AssetServer>,
+) {
+    // The rest of the interface picks its colors when it is spawned
+    if theme.is_changed() {
+        clear_color.0 = theme.get().colors.background;
+        let ground = asset_server.load(theme.get().ground());
+        for mut texture in &mut ground_query {
+            *texture = ground.clone();
+        }
+    }
+}
+
+fn dress_tiles(
+    mut tiles_query: Query<(Ref<TileType>, &mut Handle<Image>)>,
+    theme: Res<ChosenTheme>,
+    asset_server: Res<AssetServer>,
+) {
+    // Give the new tiles their texture, and all of them when the theme changes
+    for (tile_type, mut texture) in &mut tiles_query {
+        if tile_type.is_changed() || theme.is_changed() {
+            *texture = asset_server.load(theme.get().tile_texture(*tile_type));
+        }
+    }
+}
+
+fn recolor_interface(
+    theme: Res<ChosenTheme>,
+    mut background_query: Query<(&ThemeColor, &mut BackgroundColor), Without<Text>>,
+    mut text_query: Query<(&ThemeColor, &mut Text)>,
+) {
+    // The menus are rebuilt on a change, the rest of the interface is recolored
+    if !theme.is_changed() {
+        return;
+    }
+    let colors = theme.get().colors;
+    for (theme_color, mut background) in &mut background_query {
+        background.0 = theme_color.of(&colors);
+    }
+    for (theme_color, mut text) in &mut text_query {
+        for section in &mut text.sections {
+            section.style.color = theme_color.of(&colors);
+        }
+    }
+}
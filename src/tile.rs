@@ -1,182 +1,275 @@
 use bevy::prelude::*;
-use rand::seq::SliceRandom;
-use rand::thread_rng;
-use std::f32::consts::PI;
+use rand::Rng;
 
-use crate::movement::CanMove;
+use crate::board::TileInfo;
+use crate::movement::{CanMove, Direction};
+use crate::sound::SoundEffect;
+use crate::tile_registry::{TileBehaviour, TileKind, TILE_REGISTRY};
+use crate::topology::Topology;
+use crate::GamePhase;
 use crate::GridPosition;
 
-pub const TILE_SCALE: Vec3 = Vec3::new(0.3, 0.3, 0.0);
+// Not flattened on z, so that the children of a tile (like its fog back) keep their z offset
+pub const TILE_SCALE: Vec3 = Vec3::new(0.3, 0.3, 1.0);
 pub const TILE_SIZE: Vec3 = Vec3::new(900.0, 900.0, 0.0);
 
-// All of the tile types allowed by the Game
-#[derive(Debug, Default, PartialEq, Component, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, Hash, Component, Clone, Copy)]
 pub enum TileType {
-    Corner,
-    Straight,
-    T,
-    FourWay,
-    Block,
-    OneWay,
+    // A kind of the tile registry, by its index
+    Kind(usize),
+    // Any kind of the random pool of the registry
     #[default]
     Any,
+    // No tile, a hole in the board
+    Void,
 }
 
-// Which ways out of this tile are open
+impl TileType {
+    pub fn named(name: &str) -> Self {
+        // Tile kinds are referenced by their name in the registry
+        match TILE_REGISTRY.index_of(name) {
+            Some(index) => TileType::Kind(index),
+            None => panic!("Unknown tile kind: {name}"),
+        }
+    }
+
+    pub fn kind(self) -> Option<&'static TileKind> {
+        match self {
+            TileType::Kind(index) => TILE_REGISTRY.get(index),
+            TileType::Any | TileType::Void => None,
+        }
+    }
+
+    pub fn behaviour(self) -> TileBehaviour {
+        self.kind().map(|kind| kind.behaviour).unwrap_or_default()
+    }
+
+    pub fn topology(self) -> Topology {
+        self.kind().map(|kind| kind.topology).unwrap_or_default()
+    }
+}
+
+// Which ways out of this tile are open, one flag per direction
 #[derive(Component, Debug, Default, Clone, Copy)]
 pub struct OpenWays {
-    pub top: bool,
-    pub right: bool,
-    pub bottom: bool,
-    pub left: bool,
+    flags: u8,
+}
+
+impl OpenWays {
+    pub fn from_mask(mask: u8, topology: Topology) -> Self {
+        // mask as written in the tile registry
+        let directions = topology.mask_directions();
+        let mut open_ways = OpenWays::default();
+        for (i, dir) in directions.iter().enumerate() {
+            if mask & (1 << (directions.len() - 1 - i)) != 0 {
+                open_ways.flags |= 1 << *dir as u8;
+            }
+        }
+        open_ways
+    }
+
+    pub fn is_open(self, dir: Direction) -> bool {
+        self.flags & (1 << dir as u8) != 0
+    }
+}
+
+// Which ways into this tile are open
+// They only differ from the OpenWays on one-way tiles
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct EntryWays(pub OpenWays);
+
+// Which ways out of the lower passage of a bridge tile are open
+// Always closed on other tiles
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct UnderWays(pub OpenWays);
+
+// Orientation of a tile, in anti-clockwise turns from its texture
+// A turn is a quarter of a full turn on square tiles and a sixth on hex tiles,
+// so it is kept modulo 12 to fit both
+// The sprite rotation and the ways of a tile are always derived from it
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Orientation(u8);
+
+impl Orientation {
+    pub const fn new(turns: i32) -> Self {
+        Orientation(turns.rem_euclid(12) as u8)
+    }
+
+    pub fn turns(self, topology: Topology) -> u8 {
+        self.0 % topology.sides()
+    }
+
+    pub fn rotated_clockwise(self) -> Self {
+        Orientation::new(self.0 as i32 - 1)
+    }
+
+    pub fn rotated_anticlockwise(self) -> Self {
+        Orientation::new(self.0 as i32 + 1)
+    }
+
+    pub fn to_quat(self, topology: Topology) -> Quat {
+        Quat::from_rotation_z(self.turns(topology) as f32 * topology.turn_angle())
+    }
 }
 
 #[derive(Bundle, Default)]
 pub struct TileBundle {
     tile_type: TileType,
+    orientation: Orientation,
     pos: GridPosition,
     can_move: CanMove,
     sprite: SpriteBundle,
     open_ways: OpenWays,
+    entry_ways: EntryWays,
+    under_ways: UnderWays,
 }
 
 pub fn spawn_tile(
     x_pos: i32,
     y_pos: i32,
-    mut tile_type: TileType,
-    angle: f32,
-    can_move: CanMove,
+    tile_info: TileInfo,
     commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
+    topology: Topology,
+    rng: &mut impl Rng,
 ) {
-    // If tile_type is Any, pick one among a list of chosen types
+    let TileInfo {
+        mut tile_type,
+        orientation,
+        can_move,
+    } = tile_info;
+
+    // If tile_type is Any, pick one from the random pool of the registry
     if tile_type == TileType::Any {
-        tile_type = *[
-            TileType::Corner,
-            TileType::Straight,
-            TileType::T,
-            // TileType::OneWay,
-            // TileType::Block,
-            // TileType::FourWay,
-        ]
-        .choose(&mut thread_rng())
-        .unwrap_or(&TileType::Corner);
-    }
-
-    let texture_path = match tile_type {
-        TileType::Corner => "corner.png",
-        TileType::Straight => "straight.png",
-        TileType::T => "T_shape.png",
-        TileType::FourWay => "4_way.png",
-        TileType::Block => "Block.png",
-        TileType::OneWay => "1_way.png",
-        TileType::Any => "corner.png", //Should never be reached, default to corner
-    };
+        tile_type = TileType::Kind(TILE_REGISTRY.random_index(rng, topology));
+    }
 
-    let open_ways = get_ways_from_type(tile_type);
+    let (open_ways, entry_ways, under_ways) = get_oriented_ways(tile_type, orientation);
 
     commands.spawn(TileBundle {
         pos: GridPosition { x_pos, y_pos },
         tile_type,
+        orientation,
         can_move,
         sprite: SpriteBundle {
-            texture: asset_server.load(texture_path),
+            // the texture comes from the chosen theme
             transform: Transform {
-                translation: Vec3::new(
-                    x_pos as f32 * TILE_SIZE.x * TILE_SCALE.x,
-                    y_pos as f32 * TILE_SIZE.y * TILE_SCALE.y,
-                    0.0,
-                ),
+                translation: topology
+                    .to_world(&GridPosition { x_pos, y_pos })
+                    .extend(0.0),
                 scale: TILE_SCALE,
-                rotation: Quat::from_axis_angle(
-                    Vec3 {
-                        x: 0.0,
-                        y: 0.0,
-                        z: 1.0,
-                    },
-                    angle,
-                ),
+                rotation: orientation.to_quat(tile_type.topology()),
             },
             ..default()
         },
-        open_ways: rotate_ways(open_ways, angle),
+        open_ways,
+        entry_ways,
+        under_ways,
     });
 }
 
-pub fn rotate_ways(mut open_ways: OpenWays, angle: f32) -> OpenWays {
-    // rotate a tile by angle and get its new open ways
-    let old_ways = open_ways;
-    if angle == PI || angle == -PI {
-        // half-turn
-        open_ways.top = old_ways.bottom;
-        open_ways.bottom = old_ways.top;
-        open_ways.right = old_ways.left;
-        open_ways.left = old_ways.right;
-        open_ways
-    } else if angle == PI / 2.0 || angle == -3.0 * PI / 2.0 {
-        // quarter turn anti-clockwise
-        open_ways.top = old_ways.right;
-        open_ways.bottom = old_ways.left;
-        open_ways.right = old_ways.bottom;
-        open_ways.left = old_ways.top;
-        open_ways
-    } else if angle == -PI / 2.0 || angle == 3.0 * PI / 2.0 {
-        // quarter turn clockwise
-        open_ways.top = old_ways.left;
-        open_ways.bottom = old_ways.right;
-        open_ways.right = old_ways.top;
-        open_ways.left = old_ways.bottom;
-        open_ways
-    } else {
-        // println!("No rotation due to angle being {}", angle);
-        old_ways
+type RotatedTilesQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static TileType,
+        &'static Orientation,
+        &'static mut Transform,
+        &'static mut OpenWays,
+        &'static mut EntryWays,
+        &'static mut UnderWays,
+    ),
+    Changed<Orientation>,
+>;
+
+pub fn sync_tile_orientation(mut tiles_query: RotatedTilesQuery) {
+    // Derive the sprite rotation and the ways of the rotated tiles
+    for (tile_type, orientation, mut transform, mut open_ways, mut entry_ways, mut under_ways) in
+        &mut tiles_query
+    {
+        transform.rotation = orientation.to_quat(tile_type.topology());
+        (*open_ways, *entry_ways, *under_ways) = get_oriented_ways(*tile_type, *orientation);
     }
 }
 
+// The last round the rotator tiles turned for
+#[derive(Resource, Debug, Default)]
+pub struct RotatedRound(u32);
+
+pub fn reset_rotated_round(mut rotated_round: ResMut<RotatedRound>, game_state: Res<GamePhase>) {
+    // A new game starts without turning the rotators
+    rotated_round.0 = game_state.round;
+}
+
+pub fn rotate_rotators(
+    mut tiles_query: Query<(&TileType, &mut Orientation)>,
+    game_state: Res<GamePhase>,
+    mut rotated_round: ResMut<RotatedRound>,
+    mut sounds: EventWriter<SoundEffect>,
+) {
+    // Rotator tiles turn once at the end of every round
+    if rotated_round.0 == game_state.round {
+        return;
+    }
+    rotated_round.0 = game_state.round;
+
+    let mut rotated = false;
+    for (tile_type, mut orientation) in &mut tiles_query {
+        if tile_type.behaviour() == TileBehaviour::Rotator {
+            *orientation = orientation.rotated_anticlockwise();
+            rotated = true;
+        }
+    }
+    if rotated {
+        sounds.send(SoundEffect::TileRotate);
+    }
+}
+
+pub fn get_oriented_ways(
+    tile_type: TileType,
+    orientation: Orientation,
+) -> (OpenWays, EntryWays, UnderWays) {
+    let topology = tile_type.topology();
+    let under_ways = match tile_type.behaviour() {
+        TileBehaviour::Bridge(under) => OpenWays::from_mask(under, topology),
+        _ => OpenWays::default(),
+    };
+    (
+        rotate_ways(get_ways_from_type(tile_type), orientation, topology),
+        EntryWays(rotate_ways(
+            get_entries_from_type(tile_type).0,
+            orientation,
+            topology,
+        )),
+        UnderWays(rotate_ways(under_ways, orientation, topology)),
+    )
+}
+
+fn rotate_ways(open_ways: OpenWays, orientation: Orientation, topology: Topology) -> OpenWays {
+    // get the open ways of a tile after rotating it
+    // each turn anti-clockwise moves the ways to the next direction anti-clockwise
+    let directions = topology.directions();
+    let turns = orientation.turns(topology) as usize;
+    let mut rotated = OpenWays::default();
+    for (i, dir) in directions.iter().enumerate() {
+        if open_ways.is_open(*dir) {
+            rotated.flags |= 1 << directions[(i + turns) % directions.len()] as u8;
+        }
+    }
+    rotated
+}
+
 fn get_ways_from_type(tile_type: TileType) -> OpenWays {
-    match tile_type {
-        TileType::Corner => OpenWays {
-            top: false,
-            right: false,
-            bottom: true,
-            left: true,
-        },
-        TileType::Straight => OpenWays {
-            top: false,
-            right: true,
-            bottom: false,
-            left: true,
-        },
-        TileType::T => OpenWays {
-            top: false,
-            right: true,
-            bottom: true,
-            left: true,
-        },
-        TileType::FourWay => OpenWays {
-            top: true,
-            right: true,
-            bottom: true,
-            left: true,
-        },
-        TileType::OneWay => OpenWays {
-            top: false,
-            right: false,
-            bottom: true,
-            left: false,
-        },
-        TileType::Block => OpenWays {
-            top: false,
-            right: false,
-            bottom: false,
-            left: false,
-        },
+    match tile_type.kind() {
+        Some(kind) => OpenWays::from_mask(kind.openings, kind.topology),
         // Should never be reached, default to corner
-        TileType::Any => OpenWays {
-            top: false,
-            right: false,
-            bottom: true,
-            left: true,
-        },
+        None => OpenWays::from_mask(0b0011, Topology::Square),
+    }
+}
+
+fn get_entries_from_type(tile_type: TileType) -> EntryWays {
+    match tile_type.kind().and_then(|kind| kind.entries) {
+        Some(entries) => EntryWays(OpenWays::from_mask(entries, tile_type.topology())),
+        // Most tiles can be entered through their openings
+        None => EntryWays(get_ways_from_type(tile_type)),
     }
 }
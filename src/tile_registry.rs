@@ -0,0 +1,115 @@
+use once_cell::sync::Lazy;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::topology::Topology;
+
+// All of the tile kinds allowed by the Game, declared in assets/tiles.ron
+// Embedded in the binary so that it is also available in web builds
+pub static TILE_REGISTRY: Lazy<TileRegistry> = Lazy::new(|| {
+    TileRegistry::from_ron(include_str!("../assets/tiles.ron"))
+        .unwrap_or_else(|err| panic!("Invalid tile registry: {err}"))
+});
+
+#[derive(Debug, Deserialize)]
+pub struct TileKind {
+    pub name: String,
+    pub texture: String,
+    // Square tiles by default
+    #[serde(default)]
+    pub topology: Topology,
+    // Open sides before any rotation, clockwise from the top as a 0bTRBL mask (Top, Right,
+    // Bottom, Left) or from the top right on hex tiles
+    pub openings: u8,
+    // Sides the tile can be entered from, only when they differ from its openings
+    #[serde(default)]
+    pub entries: Option<u8>,
+    #[serde(default)]
+    pub random_pool: bool,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    #[serde(default)]
+    pub behaviour: TileBehaviour,
+}
+
+// What a tile does on top of its openings
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TileBehaviour {
+    #[default]
+    Plain,
+    // Linked for movement to the other tile of the same kind on the board
+    Teleporter,
+    // Has a second passage under its openings, as a mask like the openings
+    // Players stay in the passage they entered a bridge through
+    Bridge(u8),
+    // Turns once anti-clockwise at the end of every round
+    Rotator,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+#[derive(Debug)]
+pub struct TileRegistry {
+    kinds: Vec<TileKind>,
+}
+
+impl TileRegistry {
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        let kinds: Vec<TileKind> = ron::from_str(source).map_err(|err| err.to_string())?;
+
+        for (i, kind) in kinds.iter().enumerate() {
+            if kinds[..i].iter().any(|other| other.name == kind.name) {
+                return Err(format!("tile kind {} is declared twice", kind.name));
+            }
+            let under = match kind.behaviour {
+                TileBehaviour::Bridge(under) => under,
+                _ => 0,
+            };
+            let max_mask = (1 << kind.topology.sides()) - 1;
+            if kind.openings > max_mask
+                || kind.entries.is_some_and(|entries| entries > max_mask)
+                || under > max_mask
+            {
+                return Err(format!(
+                    "tile kind {} has more than {} sides",
+                    kind.name,
+                    kind.topology.sides()
+                ));
+            }
+        }
+        // Any tiles of every topology can be filled
+        for topology in [Topology::Square, Topology::Hex] {
+            let has_kinds = kinds.iter().any(|kind| kind.topology == topology);
+            if (has_kinds || topology == Topology::Square)
+                && !kinds
+                    .iter()
+                    .any(|kind| kind.topology == topology && kind.random_pool && kind.weight > 0)
+            {
+                return Err(format!("no {topology:?} tile kind can be picked at random"));
+            }
+        }
+
+        Ok(TileRegistry { kinds })
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.kinds.iter().position(|kind| kind.name == name)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&TileKind> {
+        self.kinds.get(index)
+    }
+
+    pub fn random_index(&self, rng: &mut impl Rng, topology: Topology) -> usize {
+        // Weighted pick among the kinds of the random pool that fit the board
+        let pool = (0..self.kinds.len())
+            .filter(|&i| self.kinds[i].random_pool && self.kinds[i].topology == topology)
+            .collect::<Vec<usize>>();
+        *pool
+            .choose_weighted(rng, |&i| self.kinds[i].weight)
+            .expect("The registry always has a random pool")
+    }
+}
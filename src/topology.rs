@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    movement::Direction,
+    tile::{TILE_SCALE, TILE_SIZE},
+    GridPosition,
+};
+
+// The directions of each topology, anticlockwise from the right
+const SQUARE_DIRECTIONS: [Direction; 4] = [
+    Direction::Right,
+    Direction::Up,
+    Direction::Left,
+    Direction::Down,
+];
+const HEX_DIRECTIONS: [Direction; 6] = [
+    Direction::Right,
+    Direction::UpRight,
+    Direction::UpLeft,
+    Direction::Left,
+    Direction::DownLeft,
+    Direction::DownRight,
+];
+
+// The shape of the cells of a board, and how they are laid out
+// Hex boards use axial coordinates with pointy-top cells:
+// x goes right, y goes up and to the right
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum Topology {
+    #[default]
+    Square,
+    Hex,
+}
+
+impl Topology {
+    // The neighbour directions of a cell, anticlockwise from the right
+    pub fn directions(self) -> &'static [Direction] {
+        match self {
+            Topology::Square => &SQUARE_DIRECTIONS,
+            Topology::Hex => &HEX_DIRECTIONS,
+        }
+    }
+
+    // The directions of the bits of a tile registry mask, from the highest bit
+    // (clockwise from the top, or from the top right on hex tiles)
+    pub fn mask_directions(self) -> &'static [Direction] {
+        match self {
+            Topology::Square => &[
+                Direction::Up,
+                Direction::Right,
+                Direction::Down,
+                Direction::Left,
+            ],
+            Topology::Hex => &[
+                Direction::UpRight,
+                Direction::Right,
+                Direction::DownRight,
+                Direction::DownLeft,
+                Direction::Left,
+                Direction::UpLeft,
+            ],
+        }
+    }
+
+    pub fn sides(self) -> u8 {
+        self.directions().len() as u8
+    }
+
+    // The angle of one turn of a tile (a quarter or a sixth of a full turn)
+    pub fn turn_angle(self) -> f32 {
+        std::f32::consts::TAU / self.sides() as f32
+    }
+
+    // Center of a cell in the world
+    pub fn to_world(self, pos: &GridPosition) -> Vec2 {
+        let width = TILE_SIZE.x * TILE_SCALE.x;
+        match self {
+            Topology::Square => Vec2::new(
+                pos.x_pos as f32 * width,
+                pos.y_pos as f32 * TILE_SIZE.y * TILE_SCALE.y,
+            ),
+            Topology::Hex => Vec2::new(
+                (pos.x_pos as f32 + pos.y_pos as f32 / 2.0) * width,
+                pos.y_pos as f32 * width * 3f32.sqrt() / 2.0,
+            ),
+        }
+    }
+
+    // The cell containing a world point
+    pub fn cell_at(self, world: Vec2) -> GridPosition {
+        let width = TILE_SIZE.x * TILE_SCALE.x;
+        match self {
+            Topology::Square => GridPosition {
+                x_pos: (world.x / width).round() as i32,
+                y_pos: (world.y / (TILE_SIZE.y * TILE_SCALE.y)).round() as i32,
+            },
+            Topology::Hex => {
+                let y = world.y / (width * 3f32.sqrt() / 2.0);
+                let x = world.x / width - y / 2.0;
+                // round in cube coordinates, fixing the coordinate that moved the most
+                let z = -x - y;
+                let (mut rx, mut ry, rz) = (x.round(), y.round(), z.round());
+                let (dx, dy, dz) = ((rx - x).abs(), (ry - y).abs(), (rz - z).abs());
+                if dx > dy && dx > dz {
+                    rx = -ry - rz;
+                } else if dy > dz {
+                    ry = -rx - rz;
+                }
+                GridPosition {
+                    x_pos: rx as i32,
+                    y_pos: ry as i32,
+                }
+            }
+        }
+    }
+
+    // Number of steps between two cells, ignoring walls
+    pub fn distance(self, from: &GridPosition, to: &GridPosition) -> i32 {
+        let (dx, dy) = (to.x_pos - from.x_pos, to.y_pos - from.y_pos);
+        match self {
+            Topology::Square => dx.abs() + dy.abs(),
+            Topology::Hex => (dx.abs() + dy.abs() + (dx + dy).abs()) / 2,
+        }
+    }
+}
@@ -1,24 +1,25 @@
 use bevy::{prelude::*, utils::HashMap};
 use rand::seq::SliceRandom;
-use rand::thread_rng;
-use std::fs::read_dir;
 
 use crate::{
     actors::{get_random_pos_on_axis, GridAxis, SpawnPosition},
     board_selector::SelectedBoard,
-    movement::{get_max_coords, pos_is_external},
     phases::{end_turn, GameState},
     player::Player,
-    tile::{TileType, TILE_SCALE, TILE_SIZE},
+    rng::GameRng,
+    sound::SoundEffect,
+    teams::Teams,
+    topology::Topology,
+    treasure_catalogue::{TreasureCategory, TreasureEntry},
+    ui::ChosenLanguage,
     GamePhase, GameSettings, GridPosition,
 };
 
 const TREASURE_SCALE: Vec3 = Vec3::new(0.28, 0.28, 0.0);
-const BIG_TREASURE_SCALE: Vec3 = Vec3::new(0.9, 0.9, 0.0);
 
 #[derive(Debug, Component)]
-struct Treasure {
-    id: i32,
+pub struct Treasure {
+    pub id: i32,
 }
 
 #[derive(Bundle)]
@@ -29,9 +30,9 @@ struct TreasureBundle {
 }
 
 #[derive(Resource, Debug, Default)]
-struct TreasureLists {
+pub struct TreasureLists {
     // the to-be-collected treasure list for each player
-    lists: HashMap<i32, Vec<i32>>,
+    pub lists: HashMap<i32, Vec<i32>>,
 }
 
 #[derive(Resource, Debug, Default)]
@@ -40,9 +41,35 @@ pub struct CollectedLists {
     pub lists: HashMap<i32, Vec<i32>>,
 }
 
+// The treasures of the catalogue, a treasure id picks one of them
 #[derive(Default, Debug, Resource)]
-struct TreasureSprites {
-    list: Vec<String>,
+pub struct TreasureSprites {
+    pub list: Vec<TreasureEntry>,
+    pub categories: Vec<TreasureCategory>,
+}
+
+impl TreasureSprites {
+    // None until the catalogue is loaded
+    pub fn entry(&self, treasure_id: i32) -> Option<&TreasureEntry> {
+        if self.list.is_empty() {
+            return None;
+        }
+        self.list.get(treasure_id as usize % self.list.len())
+    }
+
+    // The name of a treasure in the chosen language, with its category if it has one
+    pub fn describe(&self, treasure_id: i32, language: &ChosenLanguage) -> Option<String> {
+        let entry = self.entry(treasure_id)?;
+        let name = entry.name.get(language);
+        let category = entry
+            .category
+            .as_ref()
+            .and_then(|category| self.categories.iter().find(|other| &other.id == category));
+        Some(match category {
+            Some(category) => format!("{name} ({})", category.name.get(language)),
+            None => name.to_string(),
+        })
+    }
 }
 
 pub struct TreasurePlugin;
@@ -58,12 +85,7 @@ impl Plugin for TreasurePlugin {
             )
             .add_systems(
                 Update,
-                (
-                    move_treasure_with_ext_tile,
-                    collect_treasure,
-                    display_current_treasure,
-                )
-                    .run_if(in_state(GameState::Playing)),
+                (collect_treasure, dress_treasures).run_if(in_state(GameState::Playing)),
             )
             .add_systems(OnExit(GameState::Playing), (cleanup_treasures, reset_lists));
     }
@@ -73,19 +95,8 @@ fn spawn_all_treasures(
     mut commands: Commands,
     game_settings: Res<GameSettings>,
     selected_board: Res<SelectedBoard>,
-    mut treasure_sprites: ResMut<TreasureSprites>,
-    asset_server: Res<AssetServer>,
+    mut game_rng: ResMut<GameRng>,
 ) {
-    // all of the available treasure sprites
-    let sprite_paths = read_dir("assets/treasures/")
-        .unwrap()
-        .filter_map(|e| e.ok())
-        .map(|e| e.path().to_string_lossy().into_owned())
-        .map(|s| s.replace("assets/", ""))
-        .collect::<Vec<_>>();
-
-    treasure_sprites.list = sprite_paths.clone();
-
     // the number of set treasure positions in the selected board
     let num_set_pos: i32 = selected_board
         .board
@@ -120,30 +131,37 @@ fn spawn_all_treasures(
                     x_pos,
                     y_pos,
                     &mut commands,
-                    &asset_server,
-                    &sprite_paths[id as usize % sprite_paths.len()],
-                    false,
+                    selected_board.board.topology,
                 );
                 used_pos.push(GridPosition { x_pos, y_pos });
             }
             SpawnPosition::Any => {
                 // get random unused position
-                let mut x_pos = get_random_pos_on_axis(GridAxis::X, &selected_board);
-                let mut y_pos = get_random_pos_on_axis(GridAxis::Y, &selected_board);
+                let mut x_pos =
+                    get_random_pos_on_axis(GridAxis::X, &selected_board, &mut game_rng.treasures);
+                let mut y_pos =
+                    get_random_pos_on_axis(GridAxis::Y, &selected_board, &mut game_rng.treasures);
                 while all_player_spawns.contains(&GridPosition { x_pos, y_pos })
                     || used_pos.contains(&GridPosition { x_pos, y_pos })
+                    || selected_board.board.is_void(&GridPosition { x_pos, y_pos })
                 {
-                    x_pos = get_random_pos_on_axis(GridAxis::X, &selected_board);
-                    y_pos = get_random_pos_on_axis(GridAxis::Y, &selected_board);
+                    x_pos = get_random_pos_on_axis(
+                        GridAxis::X,
+                        &selected_board,
+                        &mut game_rng.treasures,
+                    );
+                    y_pos = get_random_pos_on_axis(
+                        GridAxis::Y,
+                        &selected_board,
+                        &mut game_rng.treasures,
+                    );
                 }
                 spawn_treasure(
                     id,
                     x_pos,
                     y_pos,
                     &mut commands,
-                    &asset_server,
-                    &sprite_paths[id as usize % sprite_paths.len()],
-                    false,
+                    selected_board.board.topology,
                 );
                 used_pos.push(GridPosition { x_pos, y_pos });
             }
@@ -151,97 +169,79 @@ fn spawn_all_treasures(
     }
 }
 
-fn spawn_treasure(
-    id: i32,
-    x_pos: i32,
-    y_pos: i32,
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    sprite_path: &String,
-    use_big_scale: bool,
-) {
+fn spawn_treasure(id: i32, x_pos: i32, y_pos: i32, commands: &mut Commands, topology: Topology) {
+    // The image comes from the catalogue
     commands.spawn(TreasureBundle {
         treasure: Treasure { id },
         pos: GridPosition { x_pos, y_pos },
         sprite: SpriteBundle {
             transform: Transform {
-                translation: Vec3::new(
-                    x_pos as f32 * TILE_SIZE.x * TILE_SCALE.x,
-                    y_pos as f32 * TILE_SIZE.y * TILE_SCALE.y,
-                    2.0,
-                ),
-                scale: if use_big_scale {
-                    BIG_TREASURE_SCALE
-                } else {
-                    TREASURE_SCALE
-                },
+                translation: topology
+                    .to_world(&GridPosition { x_pos, y_pos })
+                    .extend(2.0),
+                scale: TREASURE_SCALE,
                 ..default()
             },
-            texture: asset_server.load(sprite_path),
             ..default()
         },
     });
 }
 
+fn dress_treasures(
+    mut commands: Commands,
+    mut treasure_query: Query<(Entity, Ref<Treasure>, &mut Handle<Image>)>,
+    treasure_sprites: Res<TreasureSprites>,
+    asset_server: Res<AssetServer>,
+    language: Res<ChosenLanguage>,
+) {
+    // Give the new treasures their image and name, and all of them once the catalogue changes
+    for (entity, treasure, mut texture) in &mut treasure_query {
+        if !(treasure.is_added() || treasure_sprites.is_changed() || language.is_changed()) {
+            continue;
+        }
+        if let Some(entry) = treasure_sprites.entry(treasure.id) {
+            *texture = asset_server.load(&entry.file);
+            commands
+                .entity(entity)
+                .insert(Name::new(entry.name.get(&language).to_string()));
+        }
+    }
+}
+
 fn init_treasure_lists(
     mut treasure_lists: ResMut<TreasureLists>,
     mut collected_lists: ResMut<CollectedLists>,
     game_settings: Res<GameSettings>,
+    mut game_rng: ResMut<GameRng>,
 ) {
-    // Init the to-be-collected treasure lists
-
-    let mut rng = thread_rng();
+    // Init the to-be-collected treasure lists, one for each team
+    // (each player is their own team when not playing in teams)
 
     // shuffled list of all of the valid treasure ids
     let mut all_treasure_ids =
         (0..game_settings.num_players * game_settings.treasures_to_get).collect::<Vec<i32>>();
-    all_treasure_ids.shuffle(&mut rng);
+    all_treasure_ids.shuffle(&mut game_rng.deal);
 
-    for (player_id, chunk) in all_treasure_ids
-        .chunks(game_settings.treasures_to_get.try_into().unwrap())
-        .enumerate()
-    {
-        // iterate on chunks of size treasures_to_get of the shuffled list of ids
-        treasure_lists
-            .lists
-            .insert(player_id.try_into().unwrap(), chunk.to_vec());
-
-        // Init the collected treasures list for this player
-        collected_lists
-            .lists
-            .insert(player_id.try_into().unwrap(), vec![]);
+    let mut remaining_ids = all_treasure_ids.as_slice();
+    for team in game_settings.teams.team_ids(game_settings.num_players) {
+        // a team gets treasures_to_get treasures per member
+        let num_members = game_settings
+            .teams
+            .members(team, game_settings.num_players)
+            .len();
+        let (deck, rest) =
+            remaining_ids.split_at(game_settings.treasures_to_get as usize * num_members);
+        treasure_lists.lists.insert(team, deck.to_vec());
+        remaining_ids = rest;
     }
-}
-
-fn move_treasure_with_ext_tile(
-    tiles_query: Query<&GridPosition, With<TileType>>,
-    mut treasures_query: Query<(&mut GridPosition, &mut Transform, &Treasure), Without<TileType>>,
-    selected_board: Res<SelectedBoard>,
-) {
-    // If the external tile had a treasure on it, move that treasure along with the external tile
-
-    let (max_x, max_y) = get_max_coords(&selected_board);
-
-    for tile_grid_pos in &tiles_query {
-        if pos_is_external(tile_grid_pos, max_x, max_y) {
-            // This is the external tile
-            for (mut treasure_grid_pos, mut treasure_transform, _treasure) in &mut treasures_query {
-                if pos_is_external(&treasure_grid_pos, max_x, max_y) {
-                    // This is the external treasure
 
-                    *treasure_grid_pos = *tile_grid_pos;
-                    // Make it follow the external tile
-                    treasure_transform.translation = Vec3::new(
-                        tile_grid_pos.x_pos as f32 * TILE_SIZE.x * TILE_SCALE.x,
-                        tile_grid_pos.y_pos as f32 * TILE_SIZE.y * TILE_SCALE.y,
-                        2.0,
-                    )
-                }
-            }
-        }
+    for player_id in 0..game_settings.num_players {
+        // Init the collected treasures list for this player
+        collected_lists.lists.insert(player_id, vec![]);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn collect_treasure(
     mut commands: Commands,
     player_query: Query<(&Player, &GridPosition)>,
@@ -249,35 +249,44 @@ fn collect_treasure(
     mut treasure_lists: ResMut<TreasureLists>,
     mut collected_lists: ResMut<CollectedLists>,
     mut game_state: ResMut<GamePhase>,
+    game_settings: Res<GameSettings>,
+    mut sounds: EventWriter<SoundEffect>,
 ) {
     if !game_state.has_ended {
         // don't do any collection if game has ended
         for (player, p_grid_pos) in &player_query {
             if player.id == game_state.current_player_id {
                 // Cannot collect a treasure outside of a player's turn
+                let team = game_settings.teams.team_of(player.id);
                 for (treasure, t_entity, t_grid_pos) in &treasure_query {
                     if p_grid_pos == t_grid_pos {
-                        let player_treasure_list = treasure_lists.lists.get(&player.id).unwrap();
-                        if player_treasure_list
-                            .iter()
-                            .position(|&id| id == treasure.id)
-                            == Some(player_treasure_list.len() - 1)
-                        {
-                            // if this is the treasure to be collected for this player,
+                        let team_treasure_list = treasure_lists.lists.get_mut(&team).unwrap();
+                        let position = team_treasure_list.iter().position(|&id| id == treasure.id);
+                        // Alone, the treasures are collected in order
+                        // In a team, any treasure of the team's deck can be collected
+                        let collectable = match game_settings.teams {
+                            Teams::Solo => {
+                                position.is_some_and(|i| i + 1 == team_treasure_list.len())
+                            }
+                            Teams::Pairs { .. } | Teams::Coop => position.is_some(),
+                        };
+                        if let (true, Some(position)) = (collectable, position) {
+                            // if this is a treasure to be collected for this player,
                             // collect it and stop moving
                             commands.entity(t_entity).despawn_recursive();
-                            let just_collected = treasure_lists
-                                .lists
-                                .get_mut(&player.id)
-                                .unwrap()
-                                .pop()
-                                .unwrap();
+                            let just_collected = team_treasure_list.remove(position);
                             collected_lists
                                 .lists
                                 .get_mut(&player.id)
                                 .unwrap()
                                 .push(just_collected);
-                            end_turn(&mut game_state, &collected_lists);
+                            sounds.send(SoundEffect::TreasurePickup);
+                            end_turn(
+                                &mut game_state,
+                                &treasure_lists,
+                                &game_settings,
+                                &mut sounds,
+                            );
                         }
                     }
                 }
@@ -286,40 +295,6 @@ fn collect_treasure(
     }
 }
 
-fn display_current_treasure(
-    mut commands: Commands,
-    treasure_query: Query<(&Treasure, Entity)>,
-    treasure_lists: Res<TreasureLists>,
-    game_state: Res<GamePhase>,
-    asset_server: Res<AssetServer>,
-    keys: Res<Input<KeyCode>>,
-    sprite_paths: Res<TreasureSprites>,
-) {
-    if keys.just_pressed(KeyCode::Space) {
-        let id = *treasure_lists
-            .lists
-            .get(&game_state.current_player_id)
-            .unwrap()
-            .last()
-            .unwrap();
-        spawn_treasure(
-            -1,
-            -2,
-            2,
-            &mut commands,
-            &asset_server,
-            &sprite_paths.list[id as usize],
-            true,
-        );
-    } else if keys.just_released(KeyCode::Space) {
-        for (treasure, entity) in &treasure_query {
-            if treasure.id == -1 {
-                commands.entity(entity).despawn_recursive();
-            }
-        }
-    }
-}
-
 fn cleanup_treasures(mut commands: Commands, treasure_query: Query<Entity, With<Treasure>>) {
     for entity in &treasure_query {
         commands.entity(entity).despawn_recursive();
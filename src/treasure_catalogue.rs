@@ -0,0 +1,159 @@
+use std::fmt;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AssetPath, AsyncReadExt, LoadContext},
+    prelude::*,
+    reflect::TypePath,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{treasure::TreasureSprites, ui::LocalizedName};
+
+// The catalogue the game starts from, in the assets folder
+// Read through the asset server, so that it is found the same way on the web
+const CATALOGUE_PATH: &str = "treasures/base.catalogue.ron";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TreasureCategory {
+    pub id: String,
+    pub name: LocalizedName,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TreasureEntry {
+    pub id: String,
+    // path of the image in the assets folder
+    pub file: String,
+    pub name: LocalizedName,
+    // id of one of the categories
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+// A list of treasures, with the ones of the packs it includes after its own
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct TreasureCatalogue {
+    #[serde(default)]
+    pub categories: Vec<TreasureCategory>,
+    pub treasures: Vec<TreasureEntry>,
+    #[serde(default)]
+    pub packs: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum CatalogueError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Pack(String),
+}
+
+impl fmt::Display for CatalogueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CatalogueError::Io(error) => write!(f, "could not read the catalogue: {error}"),
+            CatalogueError::Ron(error) => write!(f, "invalid catalogue: {error}"),
+            CatalogueError::Pack(error) => write!(f, "invalid treasure pack: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for CatalogueError {}
+
+impl From<std::io::Error> for CatalogueError {
+    fn from(error: std::io::Error) -> Self {
+        CatalogueError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for CatalogueError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        CatalogueError::Ron(error)
+    }
+}
+
+#[derive(Default)]
+struct TreasureCatalogueLoader;
+
+impl AssetLoader for TreasureCatalogueLoader {
+    type Asset = TreasureCatalogue;
+    type Settings = ();
+    type Error = CatalogueError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<TreasureCatalogue, CatalogueError>> {
+        Box::pin(async move {
+            let mut bytes = vec![];
+            reader.read_to_end(&mut bytes).await?;
+            let mut catalogue: TreasureCatalogue = ron::de::from_bytes(&bytes)?;
+
+            // The packs are merged in, a treasure or category declared twice keeps its first entry
+            for pack_path in std::mem::take(&mut catalogue.packs) {
+                let pack_bytes = load_context
+                    .read_asset_bytes(AssetPath::from(pack_path.clone()))
+                    .await
+                    .map_err(|error| CatalogueError::Pack(format!("{pack_path}: {error}")))?;
+                let pack: TreasureCatalogue = ron::de::from_bytes(&pack_bytes)
+                    .map_err(|error| CatalogueError::Pack(format!("{pack_path}: {error}")))?;
+                for category in pack.categories {
+                    if catalogue
+                        .categories
+                        .iter()
+                        .all(|other| other.id != category.id)
+                    {
+                        catalogue.categories.push(category);
+                    }
+                }
+                for entry in pack.treasures {
+                    if catalogue.treasures.iter().all(|other| other.id != entry.id) {
+                        catalogue.treasures.push(entry);
+                    }
+                }
+            }
+            Ok(catalogue)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["catalogue.ron"]
+    }
+}
+
+#[derive(Resource, Debug)]
+struct CatalogueHandle(Handle<TreasureCatalogue>);
+
+pub struct TreasureCataloguePlugin;
+
+impl Plugin for TreasureCataloguePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<TreasureCatalogue>()
+            .init_asset_loader::<TreasureCatalogueLoader>()
+            .add_systems(Startup, load_catalogue)
+            .add_systems(Update, update_treasure_sprites);
+    }
+}
+
+fn load_catalogue(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CatalogueHandle(asset_server.load(CATALOGUE_PATH)));
+}
+
+fn update_treasure_sprites(
+    mut events: EventReader<AssetEvent<TreasureCatalogue>>,
+    catalogues: Res<Assets<TreasureCatalogue>>,
+    handle: Res<CatalogueHandle>,
+    mut treasure_sprites: ResMut<TreasureSprites>,
+) {
+    // Once loaded, and again whenever the file changes
+    for event in events.read() {
+        if event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0) {
+            if let Some(catalogue) = catalogues.get(&handle.0) {
+                treasure_sprites.list = catalogue.treasures.clone();
+                treasure_sprites.categories = catalogue.categories.clone();
+            }
+        }
+    }
+}
@@ -1,18 +1,38 @@
 use bevy::prelude::*;
+use serde::Deserialize;
 
-use crate::phases::GameState;
+use crate::{
+    phases::GameState,
+    theme::{ChosenTheme, ThemeColor},
+};
 
 const FONT_SIZE: f32 = 35.0;
 
 #[derive(Debug)]
-enum Language {
+pub enum Language {
     French,
     English,
 }
 
 #[derive(Resource, Debug)]
-struct ChosenLanguage {
-    lang: Language,
+pub struct ChosenLanguage {
+    pub lang: Language,
+}
+
+// A text in each language of the game
+#[derive(Debug, Clone, Deserialize)]
+pub struct LocalizedName {
+    pub en: String,
+    pub fr: String,
+}
+
+impl LocalizedName {
+    pub fn get(&self, language: &ChosenLanguage) -> &str {
+        match language.lang {
+            Language::French => &self.fr,
+            Language::English => &self.en,
+        }
+    }
 }
 
 #[derive(Component, Debug)]
@@ -30,10 +50,14 @@ impl Plugin for UIPlugin {
     }
 }
 
-fn spawn_controls_text(mut commands: Commands, language: Res<ChosenLanguage>) {
+fn spawn_controls_text(
+    mut commands: Commands,
+    language: Res<ChosenLanguage>,
+    theme: Res<ChosenTheme>,
+) {
     let text_style = TextStyle {
         font_size: FONT_SIZE,
-        color: Color::GOLD,
+        color: theme.get().colors.text,
         ..default()
     };
 
@@ -47,15 +71,15 @@ fn spawn_controls_text(mut commands: Commands, language: Res<ChosenLanguage>) {
     match language.lang {
         Language::French => {
             commands.spawn((TextBundle::from_section(
-                "Fleches: Deplacer Tuile/Joueur\nR: Rotation de Tuile\nS: Simuler un deplacement de Tuile\nEntree: Pousser la Tuile\nT: Terminer son tour\nEspace: Afficher le tresor actuel\nPageUp/PageDown: Zoom",
+                "Fleches: Deplacer Tuile/Joueur\nMaj+Haut/Bas: Autre diagonale (plateau hexagonal)\nE: Prendre un teleporteur\nR / Maj+R: Rotation de Tuile\nC: Changer de tuile de reserve\nS: Simuler un deplacement de Tuile\nEntree: Pousser la Tuile\nT: Terminer son tour\n1-3: Jouer une carte pouvoir\nEspace (maintenu): Afficher le tresor actuel\nTab (maintenu): Afficher tous ses tresors\nPageUp/PageDown/Molette: Zoom\nClic droit: Deplacer la vue\nF: Recadrer le plateau\nM: Couper le son",
                 text_style,
-            ).with_style(ui_style), ControlsText));
+            ).with_style(ui_style), ControlsText, ThemeColor::Text));
         }
         Language::English => {
             commands.spawn((TextBundle::from_section(
-                "Arrows: Move Tile/Player\nR: Rotate Tile\nS: Simulate a push\nReturn: Push Tile\nT: End turn\nSpace: Display current treasure\nPgUp/PgDown: Zoom",
+                "Arrows: Move Tile/Player\nShift+Up/Down: Other diagonal (hex board)\nE: Use a teleporter\nR / Shift+R: Rotate Tile\nC: Switch spare tile\nS: Simulate a push\nReturn: Push Tile\nT: End turn\n1-3: Play a power card\nSpace (hold): Display current treasure\nTab (hold): Display all your treasures\nPgUp/PgDown/Wheel: Zoom\nRight click: Pan view\nF: Fit board\nM: Mute",
                 text_style,
-            ).with_style(ui_style), ControlsText));
+            ).with_style(ui_style), ControlsText, ThemeColor::Text));
         }
     }
 }